@@ -0,0 +1,49 @@
+use std::io::Write;
+use tokio_core::reactor::Core;
+
+use super::dynamic::dynissue::DynamicIssue;
+use super::search::Searcher;
+use super::utils::{format_table, get_display_string};
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::fetch::fetcher::Fetcher;
+use crate::query::result::QueryResult;
+
+//search by any jql and print given fields of found issues as a table
+pub fn query_table<W: Write>(
+    config: &Config,
+    core: &mut Core,
+    fetcher: &mut Fetcher,
+    jql: &str,
+    fields: Vec<String>,
+    writer: &mut W,
+) -> Result<()> {
+    let mut result = QueryResult::<DynamicIssue>::default(100);
+    let catalog = fetcher.get_field_catalog(core, &config.field_uri())?;
+    let declared = catalog.resolve_all(&fields)?;
+    let uri = config.search_uri();
+    Searcher::new(core, fetcher, &uri, &config.search).perform(jql, &declared, &mut result)?;
+
+    let mut header = vec!["key".to_string()];
+    header.extend(fields.iter().cloned());
+    let rows: Vec<Vec<String>> = result
+        .issues
+        .iter()
+        .map(|issue| {
+            let mut row = vec![issue.key.clone()];
+            row.extend(fields.iter().map(|field| {
+                issue
+                    .fields
+                    .get(field)
+                    .map_or_else(String::new, get_display_string)
+            }));
+            row
+        })
+        .collect();
+    writer
+        .write_all(format_table(&header, &rows).as_bytes())
+        .map_err(|source| Error::Report {
+            path: "<output>".to_string(),
+            source,
+        })
+}
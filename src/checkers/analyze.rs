@@ -1,4 +1,6 @@
 use super::persist::{parse_from, write_to};
+use crate::config::Config;
+use crate::error::{Error, Result};
 use crate::fetch::fetcher::Fetcher;
 use crate::query::result::QueryResult;
 use serde::de::DeserializeOwned;
@@ -6,44 +8,224 @@ use serde::Serialize;
 use tokio_core::reactor::Core;
 
 use std::cmp::Ord;
-use std::fs::File;
-use std::io::BufReader;
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::Path;
 
-use super::datatypes::{ParsedData, StoredData};
+use super::datatypes::{KeyedData, ParsedData, StoredData};
+use super::dynamic::dynissue::DynamicIssue;
+use super::records::Records;
 use super::search::Searcher;
+use super::store::Store;
+use super::sync::{merge, updated_since};
+use crate::query::field::{FieldCatalog, FieldMap};
 use itertools::Itertools;
 
-const SEARCH_URI: &str = "https://jiradc.int.net.nokia.com/rest/api/2/search";
+//field catalogue of the server, kept next to the caches
+const FIELD_CATALOG_FILE: &str = "field-catalog.json";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CachePolicy {
+    //reuse local cache unless outdated, sync it otherwise
+    Reuse,
+    //always fetch all from server and overwrite local cache
+    Refresh,
+    //never fetch, local cache is used regardless of its age
+    Offline,
+    //fetch only issues updated since the cache was synced and merge them
+    Sync,
+}
+
 //skeleton function for fetch data and do analysis
 pub fn analyze<T, R, F>(
+    config: &Config,
     core: &mut Core,
-    fetcher: &mut Fetcher,
-    search: &'static str,
+    fetcher: Option<&mut Fetcher>,
+    search: &str,
     cache_fname: &str,
+    policy: CachePolicy,
     analyzer: F,
-) -> Vec<T>
+) -> Result<Vec<T>>
+where
+    T: DeserializeOwned + Serialize + StoredData<Parsed = R> + KeyedData + Ord,
+    R: DeserializeOwned + ParsedData,
+    F: Fn(&[T]) -> Result<()>,
+{
+    let items = load_items(config, core, fetcher, search, cache_fname, policy)?;
+    analyzer(&items)?;
+    Ok(items)
+}
+
+//load items from local cache or server per given policy, fetched ones are cached
+pub fn load_items<T, R>(
+    config: &Config,
+    core: &mut Core,
+    fetcher: Option<&mut Fetcher>,
+    search: &str,
+    cache_fname: &str,
+    policy: CachePolicy,
+) -> Result<Vec<T>>
 where
-    T: DeserializeOwned + Serialize + StoredData<Parsed = R> + Ord,
+    T: DeserializeOwned + Serialize + StoredData<Parsed = R> + KeyedData + Ord,
     R: DeserializeOwned + ParsedData,
-    F: Fn(&[T]) -> (),
 {
+    let fields = R::get_field_list()
+        .into_iter()
+        .map(|(key, id)| (key.to_string(), config.get_field(key, id).to_string()))
+        .collect();
+    let source = Source {
+        search,
+        fields,
+        cache_fname,
+    };
+    load_with(config, core, fetcher, &source, policy, T::parse_from)
+}
+
+//where items are fetched from and cached to
+pub struct Source<'a> {
+    pub search: &'a str,
+
+    //fetched fields as (key, name or id), issues are parsed by the keys
+    pub fields: Vec<(String, String)>,
+    pub cache_fname: &'a str,
+}
+
+//same as load_items, with fields and conversion given at runtime, e.g. by a schema
+pub fn load_with<T, R, F>(
+    config: &Config,
+    core: &mut Core,
+    fetcher: Option<&mut Fetcher>,
+    source: &Source,
+    policy: CachePolicy,
+    convert: F,
+) -> Result<Vec<T>>
+where
+    T: DeserializeOwned + Serialize + KeyedData + Ord,
+    R: DeserializeOwned,
+    F: Fn(&R) -> T,
+{
+    let (search, cache_fname) = (source.search, source.cache_fname);
+    let cached = match policy {
+        CachePolicy::Refresh | CachePolicy::Sync => None,
+        CachePolicy::Reuse => load_cache(cache_fname, true),
+        CachePolicy::Offline => load_cache(cache_fname, false),
+    };
+    if let Some(items) = cached {
+        return Ok(items);
+    }
+
+    let cache_error = |source| Error::Cache {
+        path: cache_fname.to_string(),
+        source,
+    };
+    let fetcher = fetcher.ok_or_else(|| {
+        cache_error(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no usable cache while working offline",
+        ))
+    })?;
+    let fields = resolve_fields(config, core, fetcher, source, policy)?;
+    let synced = match policy {
+        CachePolicy::Refresh => None,
+        _ => load_records::<T>(cache_fname)
+            .filter(|it| it.query == search)
+            .filter(|it| it.records.iter().all(|item| !item.get_key().is_empty())),
+    };
+
+    //anything updated during the search is found again by next sync
+    let timestamp = fetcher.get_timestamp();
+    let items = match synced {
+        Some(cached) => {
+            info!("Sync items of {} since {}", cache_fname, cached.timestamp);
+            //keys first so that issues created meanwhile are kept as updated
+            let keys: Vec<DynamicIssue> =
+                search_issues(config, core, fetcher, search, &FieldMap::by_ids(&["key"]))?;
+            let keys = keys.into_iter().map(|it| it.key).collect();
+            let search = updated_since(search, cached.timestamp, timestamp);
+            let updated = search_issues(config, core, fetcher, &search, &fields)?;
+            merge(
+                cached.records,
+                updated.iter().map(&convert).collect(),
+                &keys,
+            )
+        }
+        None => {
+            let issues = search_issues(config, core, fetcher, search, &fields)?;
+            if issues.is_empty() {
+                info!("No items found, cached as empty for {}", cache_fname);
+            }
+            issues.iter().map(&convert).collect()
+        }
+    };
+    let records = Records::synced(search, timestamp, items.into_iter().sorted());
+    if let Some(database) = &config.history.database {
+        Store::open(database)?.save(cache_fname, &records)?;
+    }
+    let file = File::create(cache_fname).map_err(cache_error)?;
+    match write_to(file, records) {
+        (Ok(_), items) => Ok(items),
+        (Err(reason), _) => Err(cache_error(io::Error::other(reason))),
+    }
+}
+
+//fields resolved by the catalogue saved along the cache, which is fetched
+// again when refreshing or when it doesn't know some of the fields
+fn resolve_fields(
+    config: &Config,
+    core: &mut Core,
+    fetcher: &mut Fetcher,
+    source: &Source,
+    policy: CachePolicy,
+) -> Result<FieldMap> {
+    let path = Path::new(source.cache_fname).with_file_name(FIELD_CATALOG_FILE);
+    if policy != CachePolicy::Refresh {
+        let saved = fs::read_to_string(&path)
+            .ok()
+            .and_then(|it| FieldCatalog::parse(&it).ok())
+            .and_then(|it| it.resolve_keyed(&source.fields).ok());
+        if let Some(fields) = saved {
+            return Ok(fields);
+        }
+    }
+    let catalog = fetcher.get_field_catalog(core, &config.field_uri())?;
+    fs::write(&path, catalog.to_json()?).map_err(|source| Error::Cache {
+        path: path.display().to_string(),
+        source,
+    })?;
+    catalog.resolve_keyed(&source.fields)
+}
+
+fn search_issues<R: DeserializeOwned>(
+    config: &Config,
+    core: &mut Core,
+    fetcher: &mut Fetcher,
+    search: &str,
+    fields: &FieldMap,
+) -> Result<Vec<R>> {
     let mut result = QueryResult::<R>::default(100);
-    let fields = R::get_field_list();
-
-    use std::io::{Error, ErrorKind};
-    let items = File::open(cache_fname)
-        .and_then(|f| {
-            parse_from(BufReader::new(f))
-                .map(|rcs| rcs.records)
-                .map_err(|_x| Error::new(ErrorKind::Other, "not interested"))
-        })
-        .or_else(|_x| -> Result<Vec<T>, Error> {
-            Searcher::new(core, fetcher, SEARCH_URI, vec![]).perform(search, fields, &mut result);
-            let items: Vec<T> = result.issues.iter().map(|it| T::parse_from(it)).collect();
-            let items = items.into_iter().sorted();
-            Ok(write_to(File::create(cache_fname).unwrap(), items).1)
-        })
-        .unwrap();
-    analyzer(&items);
-    items
+    let uri = config.search_uri();
+    Searcher::new(core, fetcher, &uri, &config.search).perform(search, fields, &mut result)?;
+    Ok(result.issues)
+}
+
+//records with their sync information, regardless of their age
+pub(crate) fn load_records<T: DeserializeOwned>(cache_fname: &str) -> Option<Records<T>> {
+    let file = File::open(cache_fname).ok()?;
+    parse_from(BufReader::new(file), false).ok()
+}
+
+//records from local cache file, None if absent or unusable
+pub fn load_cache<T>(cache_fname: &str, check_outdated: bool) -> Option<Vec<T>>
+where
+    T: DeserializeOwned,
+{
+    match File::open(cache_fname) {
+        Ok(f) => parse_from(BufReader::new(f), check_outdated)
+            .map(|rcs| rcs.records)
+            .ok(),
+        Err(_) => {
+            warn!("No local cache available at {}", cache_fname);
+            None
+        }
+    }
 }
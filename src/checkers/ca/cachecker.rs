@@ -3,104 +3,263 @@ extern crate itertools;
 use crate::checkers::ca::timeline::get_system_split;
 use std::fmt::format;
 use std::fs::File;
-use std::io::BufWriter;
-use std::io::Write;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
 
-use super::super::fs2::fs2item::Fs2Item;
-use super::super::sys::sysitem::SysItem;
+use super::super::fs2::{fs2checker, fs2item::Fs2Item};
+use super::super::sys::{syschecker, sysitem::SysItem};
 use super::caitem::{Activity, CAItem};
+use super::gantt;
 use super::pipeline::PipelineInfo;
-use super::timeline::analyze_timeline;
-use crate::checkers::utils::get_leftmost;
+use super::timeline::{analyze_timeline, get_timelines, get_top_lead_times, TimeLineInfo};
+use crate::checkers::dashboard::Dashboard;
+use crate::checkers::fb::{Date, FbCalendar, FeatureBuild};
+use crate::checkers::plan::Effort;
+use crate::checkers::table::{cell, Table};
+use crate::checkers::utils::{write_report, NO_ITEMS};
+use crate::error::Result;
 
 use self::itertools::Itertools;
 
 pub const BANNER: &str = "================================================================================================\n";
 
-pub fn analyze_result(items: &[CAItem], sys_items: &[SysItem], _fs2_items: &[Fs2Item]) {
+const REPORTS: [&str; 4] = [
+    "ca-details-report.txt",
+    "ca-lead-time-report.txt",
+    "ca-plan-report.txt",
+    "ca-pipeline.txt",
+];
+
+const DASHBOARD: &str = "ca-dashboard.html";
+
+const GANTT: &str = "ca-gantt.html";
+
+pub fn analyze_result(
+    items: &[CAItem],
+    sys_items: &[SysItem],
+    fs2_items: &[Fs2Item],
+    calendar: &FbCalendar,
+    timestamp: u64,
+    browse_url: &str,
+    out_dir: &Path,
+) -> Result<()> {
+    let infos: Vec<PipelineInfo> = items.iter().map(PipelineInfo::from_item).collect();
+    let table = details(items, sys_items);
+    let (lead_times, top_lead_times) = lead_times(items, calendar);
+    let plan = plan_status(items, sys_items);
+    let pipeline = pipeline(&infos);
+    for it in &[&table, &lead_times, &top_lead_times, &plan, &pipeline] {
+        it.export(out_dir)?;
+    }
+    write_report(out_dir, DASHBOARD, |buf_writer| {
+        Dashboard::new("CA analysis", browse_url)
+            .section("System level features", &syschecker::details(sys_items))
+            .section("Unresolved FS2 items", &fs2checker::unsolved(fs2_items))
+            .section("CA details", &table)
+            .section("Lead time", &lead_times)
+            .section("Top 20% lead time", &top_lead_times)
+            .section("ET plan status", &plan)
+            .section("Pipeline", &pipeline)
+            .write_html(buf_writer)
+    })?;
+    let current = calendar.build_of(Date::from_timestamp(timestamp));
+    write_report(out_dir, GANTT, |buf_writer| {
+        gantt::write_html(buf_writer, &infos, calendar, current)
+    })?;
+    if items.is_empty() {
+        warn!("No CA items to analyze!");
+        for fname in &REPORTS {
+            write_report(out_dir, fname, |buf_writer| {
+                buf_writer.write_all(NO_ITEMS.as_bytes())
+            })?;
+        }
+        return Ok(());
+    }
+
     //dumping
-    let mut buf_writer = BufWriter::new(File::create("ca-details-report.txt").unwrap());
-    dump_all(&mut buf_writer, items, sys_items);
+    write_report(out_dir, REPORTS[0], |buf_writer| {
+        dump_all(buf_writer, items, &table)
+    })?;
     info!("All items' details dumped to report file!");
 
     //calcualte lead time by features
-    let mut buf_writer = BufWriter::new(File::create("ca-lead-time-report.txt").unwrap());
-    fn efs_ei(it: &CAItem) -> bool {
-        it.activity != Activity::NA
-    }
-    fn efs_sw(it: &CAItem) -> bool {
-        it.activity != Activity::NA && it.activity != Activity::ET
-    }
-    analyze_timeline(&mut buf_writer, items, "EFS-EI", &mut efs_ei);
-    analyze_timeline(&mut buf_writer, items, "EFS-SW", &mut efs_sw);
+    write_report(out_dir, REPORTS[1], |buf_writer| {
+        analyze_timeline(buf_writer, items, "EFS-EI", calendar, &mut efs_ei)?;
+        analyze_timeline(buf_writer, items, "EFS-SW", calendar, &mut efs_sw)
+    })?;
     info!("All items' lead time analyzed and dump to report file!");
 
-    let mut buf_writer = BufWriter::new(File::create("ca-plan-report.txt").unwrap());
-    analyze_plan(&mut buf_writer, items, sys_items);
+    write_report(out_dir, REPORTS[2], |buf_writer| {
+        analyze_plan(buf_writer, items, sys_items)
+    })?;
     info!("Plan status analyzed!");
 
-    let mut buf_writer = BufWriter::new(File::create("ca-pipeline.txt").unwrap());
-    generate_pipeline(&mut buf_writer, items);
+    write_report(out_dir, REPORTS[3], |buf_writer| {
+        generate_pipeline(buf_writer, items, calendar)
+    })?;
 
     info!("Analysis of CA issues finished!");
+    Ok(())
 }
 
-fn dump_all(buf_writer: &mut BufWriter<File>, items: &[CAItem], sys_items: &[SysItem]) {
-    let total = items.len();
-    let summary = format(format_args!("@@ CA analysis: {} issues in total\n", total));
-    info!("Got {} issues for this analysis", total);
-    buf_writer.write_all(summary.as_bytes()).unwrap();
+//scope of lead time analysis, named by the items taken
+type Scope = (&'static str, fn(&CAItem) -> bool);
+
+const SCOPES: [Scope; 2] = [("EFS-EI", efs_ei), ("EFS-SW", efs_sw)];
+
+fn efs_ei(it: &CAItem) -> bool {
+    it.activity != Activity::NA
+}
 
+fn efs_sw(it: &CAItem) -> bool {
+    it.activity != Activity::NA && it.activity != Activity::ET
+}
+
+//timelines of all planned features by scope, and the top 20% of them
+fn lead_times(items: &[CAItem], calendar: &FbCalendar) -> (Table, Table) {
+    let timeline_table = |name: &str| {
+        Table::new(name)
+            .pad("scope", 6)
+            .pad("feature", 10)
+            .pad_right("lead_time", 3)
+            .pad("start_first", 4)
+            .pad("start_last", 4)
+            .pad("end_first", 4)
+            .pad("end_last", 4)
+    };
+    let row = |scope: &str, fid: &str, tl: &TimeLineInfo| {
+        vec![
+            cell(&scope),
+            cell(&fid),
+            cell(&tl.lead_time),
+            cell(&tl.start_first),
+            cell(&tl.start_last),
+            cell(&tl.end_first),
+            cell(&tl.end_last),
+        ]
+    };
+    let mut all = timeline_table("ca-lead-time").pad_right("entries", 3);
+    let mut top = timeline_table("ca-lead-time-top");
+    for (scope, mut filter) in SCOPES {
+        let timelines = get_timelines(items, calendar, &mut filter);
+        for (fid, tl, entries) in &timelines {
+            let mut cells = row(scope, fid, tl);
+            cells.push(cell(entries));
+            all.push(cells);
+        }
+        for (fid, tl) in get_top_lead_times(timelines) {
+            top.push(row(scope, fid, &tl));
+        }
+    }
+    (all, top)
+}
+
+//OM system level features by whether ET is planned for them
+fn plan_status(items: &[CAItem], sys_items: &[SysItem]) -> Table {
+    let mut plan = Table::new("ca-plan-status")
+        .pad("feature", 10)
+        .pad("et_status", 12);
+    for (fid, planned) in get_plan_status(items, sys_items).1 {
+        let status = if planned { "planned" } else { "not planned" };
+        plan.push(vec![cell(&fid), cell(&status)]);
+    }
+    plan
+}
+
+//plan of every item as drawn by the pipeline report
+fn pipeline(infos: &[PipelineInfo]) -> Table {
+    let mut table = Table::new("ca-pipeline")
+        .cut("sub_id", 15)
+        .cut("description", 30)
+        .pad("activity", 3)
+        .cut("team", 8)
+        .pad("start_fb", 4)
+        .pad("end_fb", 4);
+    for it in infos {
+        let (start_fb, end_fb) = it.get_plan();
+        table.push(vec![
+            cell(&it.get_sub_id()),
+            cell(&it.get_description()),
+            cell(it.get_activity()),
+            cell(&it.get_team()),
+            cell(&start_fb),
+            cell(&end_fb),
+        ]);
+    }
+    table
+}
+
+//items with release of their system level features
+fn details(items: &[CAItem], sys_items: &[SysItem]) -> Table {
     use std::collections::HashMap;
     let mut sys_map = HashMap::with_capacity(sys_items.len());
     for it in sys_items {
         let _x = sys_map.insert(it.get_fid().to_string(), it);
     }
 
-    buf_writer.write_all(BANNER.as_bytes()).unwrap();
-    items.iter().for_each(|it| {
+    let mut table = Table::new("ca-details-report")
+        .pad("feature_id", 9)
+        .cut("sub_id", 15)
+        .pad("target", 4)
+        .cut("release", 12)
+        .pad("key", 10)
+        .pad("activity", 3)
+        .cut("team", 8)
+        .pad("start_fb", 4)
+        .pad("end_fb", 4)
+        .pad_right("efforts", 4)
+        .cut("description", 60);
+    for it in items {
         let release = sys_map
             .get(&it.feature_id)
             .map(|sys_it| sys_it.release.as_ref())
             .unwrap_or("");
-        let line = format(format_args!(
-            "{:9}|{:15}|{:4}|{:12}|{:10}|{:3}|{:8}|{:4}|{:4}|{:4}|{:60}\n",
-            it.feature_id,
-            get_leftmost(&it.sub_id, 15),
-            it.target,
-            get_leftmost(release, 12),
-            it.key,
-            it.activity,
-            get_leftmost(&it.team, 8),
-            it.start_fb,
-            it.end_fb,
-            it.efforts,
-            get_leftmost(&it.description, 60)
-        ));
-        buf_writer.write_all(line.as_bytes()).unwrap();
-    });
-    buf_writer.write_all(BANNER.as_bytes()).unwrap();
-
-    let total_efforts = items
-        .iter()
-        .map(|it| if it.efforts > 0 { it.efforts } else { 0 })
-        .sum::<i32>();
-    let unestimated = items.iter().filter(|it| it.efforts == -1).count();
-    buf_writer
-        .write_all(
-            format(format_args!(
-                "Total efforts:{}, unestimated: {}/{}[{:.1}%]",
-                total_efforts,
-                unestimated,
-                total,
-                (unestimated as f32) / (total as f32) * 100.0
-            ))
-            .as_bytes(),
-        )
-        .unwrap();
+        table.push(vec![
+            cell(&it.feature_id),
+            cell(&it.sub_id),
+            cell(&it.target),
+            cell(&release),
+            cell(&it.key),
+            cell(&it.activity),
+            cell(&it.team),
+            cell(&it.start_fb),
+            cell(&it.end_fb),
+            cell(&it.efforts),
+            cell(&it.description),
+        ]);
+    }
+    table
 }
 
-pub fn analyze_plan(buf_writer: &mut BufWriter<File>, items: &[CAItem], sys_items: &[SysItem]) {
+fn dump_all(buf_writer: &mut BufWriter<File>, items: &[CAItem], table: &Table) -> io::Result<()> {
+    let total = items.len();
+    let summary = format(format_args!("@@ CA analysis: {} issues in total\n", total));
+    info!("Got {} issues for this analysis", total);
+    buf_writer.write_all(summary.as_bytes())?;
+
+    buf_writer.write_all(BANNER.as_bytes())?;
+    table.write_text(buf_writer)?;
+    buf_writer.write_all(BANNER.as_bytes())?;
+
+    let total_efforts = items.iter().filter_map(|it| it.efforts).sum::<Effort>();
+    let unestimated = items.iter().filter(|it| it.efforts.is_none()).count();
+    buf_writer.write_all(
+        format(format_args!(
+            "Total efforts:{}, unestimated: {}/{}[{:.1}%]",
+            total_efforts,
+            unestimated,
+            total,
+            (unestimated as f32) / (total as f32) * 100.0
+        ))
+        .as_bytes(),
+    )
+}
+
+//OM system level features and whether splits of them have ET planned
+pub fn get_plan_status<'a>(
+    items: &'a [CAItem],
+    sys_items: &'a [SysItem],
+) -> (Vec<&'a str>, Vec<(&'a str, bool)>) {
     //check if everything is planned by entity level!
     let mut om_features: Vec<&str> = sys_items
         .iter()
@@ -109,16 +268,8 @@ pub fn analyze_plan(buf_writer: &mut BufWriter<File>, items: &[CAItem], sys_item
         .collect();
     om_features.sort();
 
-    let line = format(format_args!(
-        "Total {} OM system level features candidate\n",
-        om_features.len()
-    ));
-    buf_writer.write_all(line.as_bytes()).unwrap();
-    buf_writer.write_all(BANNER.as_bytes()).unwrap();
-
     //check planning status
-    let mut planned = 0;
-    let mut unplanned = 0;
+    let mut status = Vec::new();
     for (fid, mut sub_items) in &items
         .iter()
         .filter(|it| {
@@ -129,7 +280,28 @@ pub fn analyze_plan(buf_writer: &mut BufWriter<File>, items: &[CAItem], sys_item
         .group_by(|item| get_system_split(&item.sub_id))
     {
         //check if ET planned
-        let test_status = if sub_items.any(|it| it.activity == Activity::ET) {
+        status.push((fid, sub_items.any(|it| it.activity == Activity::ET)));
+    }
+    (om_features, status)
+}
+
+pub fn analyze_plan(
+    buf_writer: &mut BufWriter<File>,
+    items: &[CAItem],
+    sys_items: &[SysItem],
+) -> io::Result<()> {
+    let (om_features, status) = get_plan_status(items, sys_items);
+    let line = format(format_args!(
+        "Total {} OM system level features candidate\n",
+        om_features.len()
+    ));
+    buf_writer.write_all(line.as_bytes())?;
+    buf_writer.write_all(BANNER.as_bytes())?;
+
+    let mut planned = 0;
+    let mut unplanned = 0;
+    for (fid, et_planned) in &status {
+        let test_status = if *et_planned {
             planned += 1;
             "planned"
         } else {
@@ -138,15 +310,15 @@ pub fn analyze_plan(buf_writer: &mut BufWriter<File>, items: &[CAItem], sys_item
         };
 
         let line = format(format_args!("Fid = {}, ET status ={}\n", fid, test_status));
-        buf_writer.write_all(line.as_bytes()).unwrap();
+        buf_writer.write_all(line.as_bytes())?;
     }
 
     let line = format(format_args!(
         "ET unplanned = {}, planned ={}\n",
         planned, unplanned
     ));
-    buf_writer.write_all(line.as_bytes()).unwrap();
-    buf_writer.write_all(BANNER.as_bytes()).unwrap();
+    buf_writer.write_all(line.as_bytes())?;
+    buf_writer.write_all(BANNER.as_bytes())
 }
 
 use std::cmp::Ordering;
@@ -158,15 +330,17 @@ fn cmp_with_prefix_as_equal(prefix: &str, right: &str) -> Ordering {
     }
 }
 
-pub fn generate_pipeline(buf_writer: &mut BufWriter<File>, items: &[CAItem]) {
-    items
-        .iter()
-        .map(|item| PipelineInfo::from_item(item))
-        .for_each(|it| {
-            //TODO: calculate first and max span?
-            let line = it.generate_schedule_row(1801, 20) + "\n";
-            buf_writer.write_all(line.as_bytes()).unwrap();
-        });
+pub fn generate_pipeline(
+    buf_writer: &mut BufWriter<File>,
+    items: &[CAItem],
+    calendar: &FbCalendar,
+) -> io::Result<()> {
+    for it in items.iter().map(PipelineInfo::from_item) {
+        //TODO: calculate first and max span?
+        let line = it.generate_schedule_row(FeatureBuild::new(18, 1), 20, calendar) + "\n";
+        buf_writer.write_all(line.as_bytes())?;
+    }
+    Ok(())
 }
 
 #[cfg(test)]
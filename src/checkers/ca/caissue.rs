@@ -1,41 +1,33 @@
 use crate::checkers::datatypes::ParsedData;
+use crate::checkers::plan::Effort;
 use crate::checkers::utils::*;
 use crate::query::issue::Issue;
 use serde_json::Value;
 
-const CA_FIELDS_SUMMARY: &str = "summary";
-const CA_FIELDS_FEATUREID: &str = "customfield_37381";
-const CA_FIELDS_TEAM: &str = "customfield_38727";
-const CA_FIELDS_STARTFB: &str = "customfield_38694";
-const CA_FIELDS_ENDFB: &str = "customfield_38693";
-const CA_FIELDS_TYPE: &str = "customfield_38750";
-const CA_FIELDS_ORIG_EFF: &str = "timeoriginalestimate";
-const CA_FIELDS_TARGET: &str = "customfield_38723";
+//keys of fields with their default names, overridden by [fields] of config
+const CA_FIELDS_SUMMARY: (&str, &str) = ("summary", "summary");
+const CA_FIELDS_FEATUREID: (&str, &str) = ("feature_id", "Feature ID");
+const CA_FIELDS_TEAM: (&str, &str) = ("team", "Team");
+const CA_FIELDS_STARTFB: (&str, &str) = ("start_fb", "Start FB");
+const CA_FIELDS_ENDFB: (&str, &str) = ("end_fb", "End FB");
+const CA_FIELDS_TYPE: (&str, &str) = ("activity_type", "Activity Type");
+const CA_FIELDS_ORIG_EFF: (&str, &str) = ("original_estimate", "timeoriginalestimate");
+const CA_FIELDS_TARGET: (&str, &str) = ("target", "Target");
 
 #[derive(Deserialize, Debug, Clone)]
 #[allow(non_snake_case)]
 pub struct CAFields {
     pub summary: String,
-
-    #[serde(rename = "customfield_37381")]
     pub feature_id: Value,
-
-    #[serde(rename = "customfield_38727")]
     pub team: Value, //string or null
-
-    #[serde(rename = "customfield_38694")]
     pub start_fb: Value,
-
-    #[serde(rename = "customfield_38693")]
     pub end_fb: Value,
-
-    #[serde(rename = "customfield_38750")]
     pub activity_type: Value,
 
-    #[serde(rename = "timeoriginalestimate")]
+    #[serde(rename = "original_estimate")]
     pub original_eff: Value,
 
-    #[serde(rename = "customfield_38723")]
+    #[serde(rename = "target")]
     pub target_pt: Value,
 }
 
@@ -84,12 +76,12 @@ impl CAIssue {
         }
     }
 
-    pub fn get_efforts(&self) -> i32 {
+    pub fn get_efforts(&self) -> Option<Effort> {
         match self.fields.original_eff {
-            Value::Number(ref num) => (num.as_u64().unwrap() / 3600) as i32,
+            Value::Number(ref num) => num.as_f64().map(|it| (it / 3600.0) as Effort),
             _ => {
                 debug!("Not specififed efforts = <{}>!", self.fields.original_eff);
-                -1
+                None
             }
         }
     }
@@ -97,7 +89,7 @@ impl CAIssue {
 
 impl ParsedData for CAIssue {
     //get field lists
-    fn get_field_list() -> Vec<String> {
+    fn get_field_list() -> Vec<(&'static str, &'static str)> {
         vec![
             CA_FIELDS_FEATUREID,
             CA_FIELDS_SUMMARY,
@@ -108,8 +100,5 @@ impl ParsedData for CAIssue {
             CA_FIELDS_ORIG_EFF,
             CA_FIELDS_TARGET,
         ]
-        .iter()
-        .map(|x| x.to_string())
-        .collect()
     }
 }
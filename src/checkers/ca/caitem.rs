@@ -1,9 +1,9 @@
-const DEFAULT_FB: u32 = 9999;
-
 extern crate serde;
 extern crate serde_json;
 
-use super::super::datatypes::StoredData;
+use super::super::datatypes::{KeyedData, LintedData, StoredData};
+use super::super::fb::FeatureBuild;
+use super::super::plan::{estimated, planned_fb, Effort};
 use super::super::utils::NA_STRING;
 use super::caissue::CAIssue;
 
@@ -42,9 +42,13 @@ pub struct CAItem {
     pub description: String,
     pub feature_id: String,
     pub team: String,
-    pub start_fb: u32,
-    pub end_fb: u32,
-    pub efforts: i32,
+    //none if not planned, or given in unrecognized format
+    #[serde(default, deserialize_with = "planned_fb")]
+    pub start_fb: Option<FeatureBuild>,
+    #[serde(default, deserialize_with = "planned_fb")]
+    pub end_fb: Option<FeatureBuild>,
+    #[serde(default, deserialize_with = "estimated")]
+    pub efforts: Option<Effort>,
     pub target: String,
     pub activity: Activity,
 }
@@ -105,15 +109,8 @@ impl CAItem {
     }
 }
 
-fn convert_fb(value: &str) -> u32 {
-    if value == NA_STRING {
-        DEFAULT_FB
-    } else {
-        match u32::from_str_radix(value, 10) {
-            Ok(x) => x,
-            Err(_) => DEFAULT_FB,
-        }
-    }
+fn convert_fb(value: &str) -> Option<FeatureBuild> {
+    FeatureBuild::parse(value)
 }
 
 //Trim leftmost spaces until meaningful characters
@@ -183,6 +180,41 @@ impl StoredData for CAItem {
     }
 }
 
+impl KeyedData for CAItem {
+    fn get_key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl LintedData for CAItem {
+    fn get_id(&self) -> &str {
+        &self.key
+    }
+
+    fn lint(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        match (self.start_fb, self.end_fb) {
+            (None, None) => problems.push("not planned".to_string()),
+            (None, _) => problems.push("no start FB".to_string()),
+            (_, None) => problems.push("no end FB".to_string()),
+            (Some(start), Some(end)) if end < start => {
+                problems.push(format!("ends {} before start {}", end, start))
+            }
+            _ => {}
+        }
+        if self.efforts.is_none() {
+            problems.push("not estimated".to_string());
+        }
+        if self.team == NA_STRING {
+            problems.push("no team".to_string());
+        }
+        if self.activity == Activity::NA {
+            problems.push("unknown activity type".to_string());
+        }
+        problems
+    }
+}
+
 #[cfg(test)]
 #[allow(non_snake_case)]
 pub(crate) mod tests {
@@ -200,8 +232,9 @@ pub(crate) mod tests {
         assert_eq!(converted.summary, "Feature_ID_xxx_yyy");
         assert_eq!(converted.feature_id, "Feature_ID");
         assert_eq!(converted.team, "Team yyy");
-        assert_eq!(converted.start_fb, 1808);
-        assert_eq!(converted.end_fb, 1809);
+        assert_eq!(converted.start_fb, Some(FeatureBuild::from_code(1808)));
+        assert_eq!(converted.end_fb, Some(FeatureBuild::from_code(1809)));
+        assert_eq!(converted.efforts, Some(6));
         assert_eq!(converted.activity, Activity::SW);
     }
 
@@ -216,20 +249,38 @@ pub(crate) mod tests {
         String::from(hdr)
             + summary
             + r#"",
-                "customfield_37381":"Feature_ID",
-                "customfield_38727":""#
+                "feature_id":"Feature_ID",
+                "team":""#
             + team
             + r#"",
-                "customfield_38723":"PT4",
-                "customfield_38694":"1808",
-                "customfield_38693":"1809",
-                "timeoriginalestimate":24000,
-                "customfield_38750":{ "value": ""#
+                "target":"PT4",
+                "start_fb":"1808",
+                "end_fb":"1809",
+                "original_estimate":24000,
+                "activity_type":{ "value": ""#
             + activity
             + r#""}
         }}"#
     }
 
+    #[test]
+    fn should_lint_plan_and_estimation() {
+        let json = get_test_json("Feature_ID_xxx_yyy", "SW", "Team yyy");
+        let mut item = CAItem::from(&serde_json::from_str::<CAIssue>(&json).unwrap());
+        assert!(item.lint().is_empty(), "{:?}", item.lint());
+
+        item.start_fb = Some(FeatureBuild::from_code(1810));
+        item.end_fb = None;
+        item.efforts = None;
+        assert_eq!(item.lint(), vec!["no end FB", "not estimated"]);
+
+        item.end_fb = Some(FeatureBuild::from_code(1809));
+        assert_eq!(
+            item.lint(),
+            vec!["ends 1809 before start 1810", "not estimated"]
+        );
+    }
+
     #[test]
     fn should_parse_desc_by_space() {
         let json = get_test_json("Leading - something else", "SW", "X");
@@ -283,11 +334,11 @@ pub(crate) mod tests {
         assert_eq!(item.cmp(&item1), Ordering::Less);
 
         let mut item2 = item1.clone();
-        item2.start_fb = 1809;
+        item2.start_fb = Some(FeatureBuild::from_code(1809));
         assert!(item1 < item2);
 
         let mut item3 = item2.clone();
-        item3.end_fb = 1810;
+        item3.end_fb = Some(FeatureBuild::from_code(1810));
         assert!(item2 < item3);
 
         let mut item4 = item3.clone();
@@ -326,7 +377,7 @@ pub(crate) mod tests {
         let item = CAItem::from(&issue.unwrap());
 
         let mut item1 = item.clone();
-        item1.end_fb = 1810;
+        item1.end_fb = Some(FeatureBuild::from_code(1810));
         item1.activity = Activity::EFS;
         assert_eq!(item.cmp(&item1), Ordering::Greater);
     }
@@ -0,0 +1,337 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use super::caitem::Activity;
+use super::pipeline::{get_window, PipelineInfo};
+use super::timeline::get_system_split;
+use crate::checkers::dashboard::escape;
+use crate::checkers::fb::{FbCalendar, FeatureBuild};
+use crate::checkers::utils::get_leftmost;
+
+const LABEL_WIDTH: usize = 360;
+const BUILD_WIDTH: usize = 32;
+const ROW_HEIGHT: usize = 20;
+const HEADER_HEIGHT: usize = 48;
+
+const ACTIVITIES: [Activity; 4] = [Activity::EFS, Activity::SW, Activity::ET, Activity::NA];
+
+fn get_color(activity: &Activity) -> &'static str {
+    match activity {
+        Activity::EFS => "#4e79a7",
+        Activity::SW => "#59a14f",
+        Activity::ET => "#f28e2b",
+        Activity::NA => "#9d9d9d",
+    }
+}
+
+const NOT_PLANNED: &str = "not planned";
+const NOT_SHOWN: &str = "planned out of shown builds";
+
+//planned builds as columns of the window, clipped to it, or why there are
+// none; an open end lasts until the window ends, an end before start only
+// takes the start
+fn get_columns(
+    info: &PipelineInfo,
+    first: FeatureBuild,
+    builds: usize,
+    calendar: &FbCalendar,
+) -> Result<(usize, usize), &'static str> {
+    let (start_fb, end_fb) = info.get_plan();
+    let start_fb = start_fb
+        .filter(|it| calendar.is_valid(*it))
+        .ok_or(NOT_PLANNED)?;
+    let last = builds as i64 - 1;
+    let start = calendar.distance(first, start_fb);
+    let end = match end_fb {
+        Some(end_fb) if end_fb >= start_fb => calendar.distance(first, end_fb),
+        Some(_) => start,
+        None => last.max(start),
+    };
+    if end < 0 || start > last {
+        return Err(NOT_SHOWN);
+    }
+    Ok((start.max(0) as usize, end.min(last) as usize))
+}
+
+fn write_bar<W: Write>(
+    writer: &mut W,
+    row: usize,
+    (start, end): (usize, usize),
+    style: &str,
+    tip: &str,
+) -> io::Result<()> {
+    writeln!(
+        writer,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}><title>{}</title></rect>",
+        LABEL_WIDTH + start * BUILD_WIDTH + 1,
+        HEADER_HEIGHT + row * ROW_HEIGHT + 4,
+        (end - start + 1) * BUILD_WIDTH - 2,
+        ROW_HEIGHT - 8,
+        style,
+        escape(tip)
+    )
+}
+
+fn write_label<W: Write>(writer: &mut W, row: usize, label: &str, style: &str) -> io::Result<()> {
+    writeln!(
+        writer,
+        "<text x=\"4\" y=\"{}\" {}>{}</text>",
+        HEADER_HEIGHT + row * ROW_HEIGHT + 14,
+        style,
+        escape(label)
+    )
+}
+
+//pipeline as an svg chart, a row per item grouped by system split under a row
+// spanning the whole split, builds given by the planned ones; the current
+// build is marked if it's shown
+pub fn write_svg<W: Write>(
+    writer: &mut W,
+    infos: &[PipelineInfo],
+    calendar: &FbCalendar,
+    current: FeatureBuild,
+) -> io::Result<()> {
+    let (first, last) = get_window(infos, calendar, current).unwrap_or((current, current));
+    let builds: Vec<FeatureBuild> = calendar.range(first, last).collect();
+
+    let mut splits: BTreeMap<&str, Vec<&PipelineInfo>> = BTreeMap::new();
+    for it in infos {
+        splits
+            .entry(get_system_split(it.get_sub_id()))
+            .or_default()
+            .push(it);
+    }
+    let rows = splits.len() + infos.len();
+    let (width, height) = (
+        LABEL_WIDTH + builds.len() * BUILD_WIDTH,
+        HEADER_HEIGHT + rows.max(1) * ROW_HEIGHT,
+    );
+    writeln!(
+        writer,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+         font-family=\"sans-serif\" font-size=\"11\">",
+        width, height
+    )?;
+
+    //legend and builds
+    for (index, activity) in ACTIVITIES.iter().enumerate() {
+        writeln!(
+            writer,
+            "<rect x=\"{}\" y=\"6\" width=\"10\" height=\"10\" fill=\"{}\"/>\
+             <text x=\"{}\" y=\"15\">{}</text>",
+            4 + index * 48,
+            get_color(activity),
+            18 + index * 48,
+            activity
+        )?;
+    }
+    for (index, fb) in builds.iter().enumerate() {
+        let x = LABEL_WIDTH + index * BUILD_WIDTH;
+        if *fb == current {
+            writeln!(
+                writer,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#fdd\"/>\
+                 <text x=\"{}\" y=\"20\" fill=\"#c00\" text-anchor=\"middle\">now</text>",
+                x,
+                HEADER_HEIGHT,
+                BUILD_WIDTH,
+                height - HEADER_HEIGHT,
+                x + BUILD_WIDTH / 2
+            )?;
+        }
+        //years start with a darker line
+        let stroke = if fb.get_build() == 1 { "#888" } else { "#ddd" };
+        writeln!(
+            writer,
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\"/>\
+             <text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+            x,
+            HEADER_HEIGHT - 16,
+            x,
+            height,
+            stroke,
+            x + BUILD_WIDTH / 2,
+            HEADER_HEIGHT - 4,
+            fb
+        )?;
+    }
+
+    let mut row = 0;
+    for (split, items) in &splits {
+        writeln!(
+            writer,
+            "<rect x=\"0\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#eee\" fill-opacity=\"0.6\"/>",
+            HEADER_HEIGHT + row * ROW_HEIGHT,
+            width,
+            ROW_HEIGHT
+        )?;
+        write_label(writer, row, split, "font-weight=\"bold\"")?;
+        let columns: Vec<(usize, usize)> = items
+            .iter()
+            .filter_map(|it| get_columns(it, first, builds.len(), calendar).ok())
+            .collect();
+        let start = columns.iter().map(|it| it.0).min();
+        let end = columns.iter().map(|it| it.1).max();
+        if let (Some(start), Some(end)) = (start, end) {
+            let tip = format!("{}: {} - {}", split, builds[start], builds[end]);
+            write_bar(writer, row, (start, end), "fill=\"#666\"", &tip)?;
+        }
+        row += 1;
+
+        for it in items {
+            let (start_fb, end_fb) = it.get_plan();
+            let label = format!(
+                "  {} {} {}",
+                get_leftmost(it.get_sub_id(), 20),
+                it.get_activity(),
+                get_leftmost(it.get_team(), 12)
+            );
+            write_label(writer, row, &label, "")?;
+            let color = get_color(it.get_activity());
+            let end = end_fb.map_or("open".to_string(), |it| it.to_string());
+            let tip = match start_fb {
+                Some(start_fb) => format!(
+                    "{} {}: {} - {}, {}",
+                    it.get_sub_id(),
+                    it.get_team(),
+                    start_fb,
+                    end,
+                    it.get_description()
+                ),
+                None => format!("{} {}: not planned", it.get_sub_id(), it.get_team()),
+            };
+            match get_columns(it, first, builds.len(), calendar) {
+                Ok(columns) => {
+                    let style = match (start_fb, end_fb) {
+                        (Some(start_fb), Some(end_fb)) if end_fb < start_fb => {
+                            format!("fill=\"{}\" stroke=\"#c00\" stroke-width=\"2\"", color)
+                        }
+                        (_, None) => {
+                            format!("fill=\"{}\" fill-opacity=\"0.5\" stroke=\"{}\" stroke-dasharray=\"4 2\"", color, color)
+                        }
+                        _ => format!("fill=\"{}\"", color),
+                    };
+                    write_bar(writer, row, columns, &style, &tip)?;
+                }
+                Err(reason) => {
+                    writeln!(
+                        writer,
+                        "<text x=\"{}\" y=\"{}\" fill=\"#999\" font-style=\"italic\">\
+                         {}<title>{}</title></text>",
+                        LABEL_WIDTH + 4,
+                        HEADER_HEIGHT + row * ROW_HEIGHT + 14,
+                        reason,
+                        escape(&tip)
+                    )?;
+                }
+            }
+            row += 1;
+        }
+    }
+    writeln!(writer, "</svg>")
+}
+
+//page of the svg chart, no external assets needed
+pub fn write_html<W: Write>(
+    writer: &mut W,
+    infos: &[PipelineInfo],
+    calendar: &FbCalendar,
+    current: FeatureBuild,
+) -> io::Result<()> {
+    writeln!(
+        writer,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>CA pipeline</title>\n</head>\n<body style=\"font-family: sans-serif\">\n\
+         <h1>CA pipeline</h1>"
+    )?;
+    write_svg(writer, infos, calendar, current)?;
+    writeln!(writer, "</body>\n</html>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::caissue::CAIssue;
+    use super::super::caitem::tests::get_test_json;
+    use super::super::caitem::CAItem;
+    use super::*;
+
+    fn get_item(summary: &str, activity: &str, plan: (Option<u32>, Option<u32>)) -> CAItem {
+        let json = get_test_json(summary, activity, "X");
+        let mut item = CAItem::from(&serde_json::from_str::<CAIssue>(&json).unwrap());
+        item.start_fb = plan.0.map(FeatureBuild::from_code);
+        item.end_fb = plan.1.map(FeatureBuild::from_code);
+        item
+    }
+
+    fn render(items: &[CAItem], current: u32) -> String {
+        let infos: Vec<PipelineInfo> = items.iter().map(PipelineInfo::from_item).collect();
+        let mut svg = Vec::new();
+        let calendar = FbCalendar::default();
+        write_svg(
+            &mut svg,
+            &infos,
+            &calendar,
+            FeatureBuild::from_code(current),
+        )
+        .unwrap();
+        String::from_utf8(svg).unwrap()
+    }
+
+    #[test]
+    fn should_fit_window_to_plans() {
+        let items = vec![
+            get_item("Fid-A-a - spec", "EFS", (Some(1812), Some(1901))),
+            get_item("Fid-A-b - code", "SW", (Some(1901), None)),
+            get_item("Fid-B-a - test", "ET", (None, None)),
+        ];
+        let infos: Vec<PipelineInfo> = items.iter().map(PipelineInfo::from_item).collect();
+        let current = FeatureBuild::from_code(1901);
+        let window = get_window(&infos, &FbCalendar::default(), current).unwrap();
+        assert_eq!((window.0.get_code(), window.1.get_code()), (1812, 1901));
+
+        let svg = render(&items, 1901);
+        assert!(svg.contains(">1812</text>") && svg.contains(">1901</text>"));
+        assert!(!svg.contains(">1811</text>") && !svg.contains(">1902</text>"));
+        assert!(svg.contains(">Fid-A</text>") && svg.contains(">Fid-B</text>"));
+        assert!(svg.contains(&format!("fill=\"{}\"", get_color(&Activity::EFS))));
+        assert!(svg.contains("stroke-dasharray"));
+        assert!(svg.contains("not planned<title>"));
+        assert!(svg.contains(">now</text>"));
+        assert!(!render(&items, 1905).contains(">now</text>"));
+    }
+
+    #[test]
+    fn should_cap_window_around_current_build() {
+        let items = vec![
+            get_item("Fid-A-a - spec", "EFS", (Some(1001), Some(1002))),
+            get_item("Fid-A-b - code", "SW", (Some(1801), Some(1905))),
+            get_item("Fid-A-c - test", "ET", (Some(1901), Some(9913))),
+        ];
+        let infos: Vec<PipelineInfo> = items.iter().map(PipelineInfo::from_item).collect();
+        let calendar = FbCalendar::default();
+        let window = |current| {
+            let (first, last) =
+                get_window(&infos, &calendar, FeatureBuild::from_code(current)).unwrap();
+            (first.get_code(), last.get_code())
+        };
+        assert_eq!(window(1901), (1801, 1913));
+        //moved into the plans if current build is beyond them
+        assert_eq!(window(1005), (1001, 1113));
+        assert_eq!(window(9913), (9801, 9913));
+
+        let svg = render(&items, 1901);
+        assert!(svg.contains(">1801</text>") && svg.contains(">1913</text>"));
+        assert!(!svg.contains(">1713</text>") && !svg.contains(">2001</text>"));
+        assert!(svg.contains("planned out of shown builds<title>Fid-A-a"));
+        //bars clipped to the window
+        assert!(!svg.contains("width=\"-"));
+        assert!(svg.contains(&format!("width=\"{}\"", 13 * BUILD_WIDTH - 2)));
+    }
+
+    #[test]
+    fn should_show_current_build_without_plans() {
+        let items = vec![get_item("Fid-A-a - spec", "EFS", (None, None))];
+        let svg = render(&items, 2003);
+        assert!(svg.contains(">2003</text>") && svg.contains(">now</text>"));
+    }
+}
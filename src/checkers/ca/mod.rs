@@ -1,5 +1,6 @@
 pub mod cachecker;
 pub(crate) mod caissue;
 pub(crate) mod caitem;
+pub(crate) mod gantt;
 pub(crate) mod pipeline;
 pub(crate) mod timeline;
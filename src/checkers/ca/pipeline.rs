@@ -3,14 +3,19 @@ use std::fmt::format;
 //use std::fs::File;
 
 use super::caitem::{Activity, CAItem};
+use crate::checkers::fb::{FbCalendar, FeatureBuild};
 use crate::checkers::utils::get_leftmost;
+use itertools::Itertools;
+
+//builds planned for an item without end, beyond any pipeline
+const OPEN_END: i64 = 999;
 
 pub struct PipelineInfo<'a> {
     sub_id: &'a String,
     description: &'a String,
     team: &'a String,
-    start_fb: u32,
-    end_fb: u32,
+    start_fb: Option<FeatureBuild>,
+    end_fb: Option<FeatureBuild>,
     activity: &'a Activity,
     //efforts: i32,
 }
@@ -28,40 +33,56 @@ impl<'a> PipelineInfo<'a> {
         }
     }
 
-    //get its own schedule info
-    fn get_sched(&self, first_fb: u32) -> (u32, u32) {
-        let mut offset: i32 = self.start_fb as i32 - first_fb as i32;
-        if offset > 20 {
-            offset = 20;
-        }
+    pub fn get_sub_id(&self) -> &str {
+        self.sub_id
+    }
 
-        if self.end_fb < self.start_fb {
-            if offset < 0 {
-                (0, 0)
-            } else {
-                (offset as u32, 1)
-            }
-        } else {
-            let mut lead_time = self.end_fb - self.start_fb + 1;
-            if lead_time > 12 {
-                lead_time -= 87; //1901 - 1813 = 1
-            }
+    pub fn get_description(&self) -> &str {
+        self.description
+    }
+
+    pub fn get_team(&self) -> &str {
+        self.team
+    }
+
+    pub fn get_activity(&self) -> &Activity {
+        self.activity
+    }
+
+    //start and end builds, none if not planned
+    pub fn get_plan(&self) -> (Option<FeatureBuild>, Option<FeatureBuild>) {
+        (self.start_fb, self.end_fb)
+    }
 
-            if offset < 0 {
-                let span = if lead_time as i32 + offset < 0 {
-                    0
+    //get its own schedule info, none if not planned
+    fn get_sched(&self, first_fb: FeatureBuild, calendar: &FbCalendar) -> Option<(u32, u32)> {
+        let start_fb = self.start_fb?;
+        let offset = calendar.distance(first_fb, start_fb).min(OPEN_END);
+        let lead_time = match self.end_fb {
+            Some(end_fb) if end_fb < start_fb => {
+                return Some(if offset < 0 {
+                    (0, 0)
                 } else {
-                    (lead_time as i32 + offset) as u32
-                };
-                (0, span)
-            } else {
-                (offset as u32, lead_time)
+                    (offset as u32, 1)
+                });
             }
+            Some(end_fb) => calendar.lead_time(start_fb, end_fb),
+            None => OPEN_END,
+        };
+        if offset < 0 {
+            Some((0, (lead_time + offset).max(0) as u32))
+        } else {
+            Some((offset as u32, lead_time.min(OPEN_END) as u32))
         }
     }
 
     //format pipeline row by start/end
-    pub fn generate_schedule_row(&self, first_fb: u32, max_span: u32) -> String {
+    pub fn generate_schedule_row(
+        &self,
+        first_fb: FeatureBuild,
+        max_span: u32,
+        calendar: &FbCalendar,
+    ) -> String {
         let mut output = format(format_args!(
             "{:15}|{:30}|{:3}|{:8}|",
             get_leftmost(self.sub_id, 15),
@@ -70,15 +91,16 @@ impl<'a> PipelineInfo<'a> {
             get_leftmost(self.team, 8)
         ));
 
-        let (offset, span) = self.get_sched(first_fb);
-        if self.start_fb == 9999 || offset >= max_span {
-            for _i in 0..max_span - 1 {
-                output += "    |";
+        let (offset, span) = match self.get_sched(first_fb, calendar) {
+            Some((offset, span)) if offset < max_span => (offset, span),
+            _ => {
+                for _i in 0..max_span - 1 {
+                    output += "    |";
+                }
+                output += "x   ";
+                return output;
             }
-            output += "x   ";
-            return output;
-        }
-
+        };
         //not started part
         for _i in 0..offset {
             output += "    |";
@@ -108,32 +130,66 @@ impl<'a> PipelineInfo<'a> {
     }
 }
 
+//builds from the earliest to the latest one planned, none if nothing is; at
+// most two years of them from a year before the current build, so that plans
+// far off don't stretch it
+pub fn get_window(
+    infos: &[PipelineInfo],
+    calendar: &FbCalendar,
+    current: FeatureBuild,
+) -> Option<(FeatureBuild, FeatureBuild)> {
+    let valid = |fb: &FeatureBuild| calendar.is_valid(*fb);
+    let starts = infos.iter().filter_map(|it| it.start_fb).filter(valid);
+    let ends = infos
+        .iter()
+        .filter(|it| it.start_fb.is_some())
+        .filter_map(|it| it.end_fb)
+        .filter(valid);
+    let (first, last) = starts.chain(ends).minmax().into_option()?;
+    let (per_year, max_builds) = (
+        i64::from(calendar.builds_per_year),
+        i64::from(calendar.builds_per_year) * 2,
+    );
+    if calendar.lead_time(first, last) <= max_builds {
+        return Some((first, last));
+    }
+    let first = calendar
+        .add(current, -per_year)
+        .max(first)
+        .min(calendar.add(last, 1 - max_builds));
+    Some((first, calendar.add(first, max_builds - 1)))
+}
+
 #[cfg(test)]
 mod tests {
     extern crate serde_json;
     use super::super::caissue::CAIssue;
     use super::super::caitem::tests::get_test_json;
-    use super::{CAItem, PipelineInfo};
+    use super::{CAItem, FbCalendar, FeatureBuild, PipelineInfo};
+
+    fn get_sched(pipeline_info: &PipelineInfo, first_fb: u32) -> Option<(u32, u32)> {
+        pipeline_info.get_sched(FeatureBuild::from_code(first_fb), &FbCalendar::default())
+    }
 
     #[test]
     fn should_get_schedule_info_without_round() {
         let item = get_test_item();
         let pipeline_info = PipelineInfo::from_item(&item);
-        let (start, span) = pipeline_info.get_sched(1807);
+        let (start, span) = get_sched(&pipeline_info, 1807).unwrap();
         assert_eq!(start, 1);
         assert_eq!(span, 2);
-        assert_eq!(pipeline_info.start_fb, 1808);
-        assert_eq!(pipeline_info.end_fb, 1809);
+        assert_eq!(pipeline_info.start_fb, Some(FeatureBuild::from_code(1808)));
+        assert_eq!(pipeline_info.end_fb, Some(FeatureBuild::from_code(1809)));
         assert_eq!(pipeline_info.team, "X");
     }
 
     #[test]
     fn should_get_shecule_info_with_rounded_plan() {
         let mut item = get_test_item();
-        item.end_fb = 1901; //start = 1807
+        item.end_fb = Some(FeatureBuild::from_code(1901)); //start = 1807
 
         let pipeline_info = PipelineInfo::from_item(&item);
-        let (start, span) = pipeline_info.get_sched(1807);
+        let (start, span) = get_sched(&pipeline_info, 1807).unwrap();
         assert_eq!(start, 1);
         assert_eq!(span, 7);
     }
@@ -141,23 +197,21 @@ mod tests {
     #[test]
     fn should_get_shecule_info_with_unknown_plan() {
         let mut item = get_test_item();
-        item.start_fb = 9999;
-        item.end_fb = 9999; //start = 1807
+        item.start_fb = None;
+        item.end_fb = None;
 
         let pipeline_info = PipelineInfo::from_item(&item);
-        let (start, span) = pipeline_info.get_sched(1807);
-        assert_eq!(start, 20);
-        assert_eq!(span, 1);
+        assert_eq!(get_sched(&pipeline_info, 1807), None);
     }
 
     #[test]
     fn should_get_shecule_info_with_started_before_first() {
         let mut item = get_test_item();
-        item.start_fb = 1801;
-        item.end_fb = 1807;
+        item.start_fb = Some(FeatureBuild::from_code(1801));
+        item.end_fb = Some(FeatureBuild::from_code(1807));
 
         let pipeline_info = PipelineInfo::from_item(&item);
-        let (start, span) = pipeline_info.get_sched(1806);
+        let (start, span) = get_sched(&pipeline_info, 1806).unwrap();
         assert_eq!(start, 0);
         assert_eq!(span, 2);
     }
@@ -171,58 +225,59 @@ mod tests {
 
     #[test]
     fn should_generate_pipeline_item_without_rounding() {
-        check_pipeline_for(1808, 1809,
+        check_pipeline_for(Some(1808), Some(1809),
             "Fid-A-a        |description                   |SW |X       |    |    |x   |x   |    |    ");
     }
 
     #[test]
     fn should_generate_pipeline_item_with_long_leading() {
-        check_pipeline_for(1808, 1901,
+        check_pipeline_for(Some(1808), Some(1901),
             "Fid-A-a        |description                   |SW |X       |    |    |x   |x   |x   |x   ");
     }
 
     #[test]
     fn should_generate_pipeline_item_with_unplanned() {
-        check_pipeline_for(9999, 9999,
+        check_pipeline_for(None, None,
             "Fid-A-a        |description                   |SW |X       |    |    |    |    |    |x   ");
     }
 
     #[test]
     fn should_generate_pipeline_item_with_planned_until_last() {
-        check_pipeline_for(1808, 1810,
+        check_pipeline_for(Some(1808), Some(1810),
             "Fid-A-a        |description                   |SW |X       |    |    |x   |x   |x   |    ");
     }
 
     #[test]
     fn should_generate_pipeline_item_with_planned_no_ending() {
-        check_pipeline_for(1808, 9999,
+        check_pipeline_for(Some(1808), None,
             "Fid-A-a        |description                   |SW |X       |    |    |x   |x   |x   |x   ");
     }
 
     #[test]
     fn should_generate_pipeline_item_with_start_as_first() {
-        check_pipeline_for(1806, 1808,
+        check_pipeline_for(Some(1806), Some(1808),
             "Fid-A-a        |description                   |SW |X       |x   |x   |x   |    |    |    ");
     }
 
     #[test]
     fn should_generate_pipeline_item_with_done_before_first() {
-        check_pipeline_for(1801, 1802,
+        check_pipeline_for(Some(1801), Some(1802),
             "Fid-A-a        |description                   |SW |X       |    |    |    |    |    |    ");
     }
 
     #[test]
     fn should_generate_pipeline_item_with_start_before_first() {
-        check_pipeline_for(1801, 1807,
+        check_pipeline_for(Some(1801), Some(1807),
             "Fid-A-a        |description                   |SW |X       |x   |x   |    |    |    |    ");
     }
 
-    fn check_pipeline_for(start: u32, end: u32, expected: &str) {
+    fn check_pipeline_for(start: Option<u32>, end: Option<u32>, expected: &str) {
         let mut item = get_test_item();
-        item.start_fb = start;
-        item.end_fb = end;
+        item.start_fb = start.map(FeatureBuild::from_code);
+        item.end_fb = end.map(FeatureBuild::from_code);
         let p = PipelineInfo::from_item(&item);
-        let line = p.generate_schedule_row(1806, 6);
+        let first = FeatureBuild::from_code(1806);
+        let line = p.generate_schedule_row(first, 6, &FbCalendar::default());
         assert_eq!(line, expected);
     }
 }
@@ -2,64 +2,56 @@ extern crate itertools;
 
 use std::fmt::format;
 use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::{self, BufWriter, Write};
 
 use self::itertools::{Itertools, MinMaxResult};
 use super::caitem::CAItem;
+use crate::checkers::fb::{FbCalendar, FeatureBuild};
 
-struct TimeLineInfo {
-    start_first: u32,
-    start_last: u32,
-    end_first: u32,
-    end_last: u32,
-    lead_time: u32,
+pub struct TimeLineInfo {
+    pub start_first: FeatureBuild,
+    pub start_last: FeatureBuild,
+    pub end_first: FeatureBuild,
+    pub end_last: FeatureBuild,
+    pub lead_time: i64,
 }
 
 impl TimeLineInfo {
-    fn new(sf: u32, sl: u32, ef: u32, el: u32) -> TimeLineInfo {
+    fn new(
+        sf: FeatureBuild,
+        sl: FeatureBuild,
+        ef: FeatureBuild,
+        el: FeatureBuild,
+        calendar: &FbCalendar,
+    ) -> TimeLineInfo {
         TimeLineInfo {
             start_first: sf,
             start_last: sl,
             end_first: ef,
             end_last: el,
-            lead_time: TimeLineInfo::get_lead_time(sf, el),
+            lead_time: calendar.lead_time(sf, el),
         }
     }
-
-    fn get_lead_time(start_first: u32, end_last: u32) -> u32 {
-        let mut lead_time = end_last - start_first + 1;
-        if lead_time > 12 {
-            lead_time -= 87 //1901 - 1813 = 1
-        }
-        lead_time
-    }
 }
 
 pub fn analyze_timeline<F>(
     buf_writer: &mut BufWriter<File>,
     items: &[CAItem],
     hint: &str,
+    calendar: &FbCalendar,
     issue_filter: &mut F,
-) where
+) -> io::Result<()>
+where
     F: FnMut(&CAItem) -> bool,
 {
     let line = format(format_args!(
         "@@ Planned features {} lead time analysis\n",
         hint
     ));
-    buf_writer.write_all(line.as_bytes()).unwrap();
-
-    let mut planned = 0;
-    let mut timelines = Vec::new();
-    for (fid, sub_items) in &items
-        .iter()
-        .filter(|it| it.start_fb < 3000 && it.end_fb < 3000)
-        .filter(|it| issue_filter(it))
-        .group_by(|item| get_system_split(&item.sub_id))
-    {
-        let times: Vec<(u32, u32)> = sub_items.map(|it| (it.start_fb, it.end_fb)).collect();
-        let timeline = calculate_timeline(&times);
+    buf_writer.write_all(line.as_bytes())?;
 
+    let timelines = get_timelines(items, calendar, issue_filter);
+    for (fid, timeline, entries) in &timelines {
         let line = format(format_args!(
             "@@@@@@ feature:{:10}, lead_time_{}:{}, start: {} - {}, end: {} - {}, entries:{}\n",
             fid,
@@ -69,45 +61,79 @@ pub fn analyze_timeline<F>(
             timeline.start_last,
             timeline.end_first,
             timeline.end_last,
-            times.len()
+            entries
         ));
-        buf_writer.write_all(line.as_bytes()).unwrap();
-        timelines.push((fid, timeline));
-
-        planned += 1;
+        buf_writer.write_all(line.as_bytes())?;
     }
+    let planned = timelines.len();
 
     let line = format(format_args!(
         "@@ Totally planned features:{} analyzed\n",
         planned
     ));
-    buf_writer.write_all(line.as_bytes()).unwrap();
+    buf_writer.write_all(line.as_bytes())?;
 
     //TOP 20% dump
-    let top_count = (planned as f32 * 0.2) as usize;
-    let line = format(format_args!("@@ Top:{}[20%] of them as below\n", top_count));
-    buf_writer.write_all(line.as_bytes()).unwrap();
+    let top = get_top_lead_times(timelines);
+    let line = format(format_args!("@@ Top:{}[20%] of them as below\n", top.len()));
+    buf_writer.write_all(line.as_bytes())?;
+    for tl in &top {
+        let line = format(format_args!(
+            "### feature:{:10}, lead_time_{}:{}, start: {} - {}, end: {} - {}\n",
+            tl.0,
+            hint,
+            tl.1.lead_time,
+            tl.1.start_first,
+            tl.1.start_last,
+            tl.1.end_first,
+            tl.1.end_last
+        ));
+        buf_writer.write_all(line.as_bytes())?;
+    }
+    Ok(())
+}
+
+//timeline of every planned system split with number of its items
+pub fn get_timelines<'a, F>(
+    items: &'a [CAItem],
+    calendar: &FbCalendar,
+    issue_filter: &mut F,
+) -> Vec<(&'a str, TimeLineInfo, usize)>
+where
+    F: FnMut(&CAItem) -> bool,
+{
+    let mut timelines = Vec::new();
+    for (fid, sub_items) in &items
+        .iter()
+        .filter(|it| it.start_fb.is_some_and(|fb| calendar.is_valid(fb)))
+        .filter(|it| it.end_fb.is_some_and(|fb| calendar.is_valid(fb)))
+        .filter(|it| issue_filter(it))
+        .group_by(|item| get_system_split(&item.sub_id))
+    {
+        let times: Vec<(FeatureBuild, FeatureBuild)> = sub_items
+            .filter_map(|it| Some((it.start_fb?, it.end_fb?)))
+            .collect();
+        timelines.push((fid, calculate_timeline(&times, calendar), times.len()));
+    }
+    timelines
+}
+
+//20% of the timelines with the longest lead time, longest first
+pub fn get_top_lead_times<T>(timelines: Vec<(&str, TimeLineInfo, T)>) -> Vec<(&str, TimeLineInfo)> {
+    let top_count = (timelines.len() as f32 * 0.2) as usize;
     timelines
         .into_iter()
+        .map(|(fid, timeline, _)| (fid, timeline))
         .sorted_by(|tl_1, tl_2| tl_2.1.lead_time.cmp(&tl_1.1.lead_time))
-        .iter()
+        .into_iter()
         .take(top_count)
-        .for_each(|tl| {
-            let line = format(format_args!(
-                "### feature:{:10}, lead_time_{}:{}, start: {} - {}, end: {} - {}\n",
-                tl.0,
-                hint,
-                tl.1.lead_time,
-                tl.1.start_first,
-                tl.1.start_last,
-                tl.1.end_first,
-                tl.1.end_last
-            ));
-            buf_writer.write_all(line.as_bytes()).unwrap();
-        });
+        .collect()
 }
 
-fn calculate_timeline(times: &[(u32, u32)]) -> TimeLineInfo {
+fn calculate_timeline(
+    times: &[(FeatureBuild, FeatureBuild)],
+    calendar: &FbCalendar,
+) -> TimeLineInfo {
     let (start_first, start_last) = match times.iter().map(|it| it.0).minmax() {
         MinMaxResult::MinMax(first, last) => (first, last),
         MinMaxResult::OneElement(x) => (x, x),
@@ -118,7 +144,7 @@ fn calculate_timeline(times: &[(u32, u32)]) -> TimeLineInfo {
         MinMaxResult::OneElement(x) => (x, x),
         _ => panic!("unexpected!"),
     };
-    TimeLineInfo::new(start_first, start_last, end_first, end_last)
+    TimeLineInfo::new(start_first, start_last, end_first, end_last, calendar)
 }
 
 pub(crate) fn get_system_split(sub_id: &str) -> &str {
@@ -0,0 +1,346 @@
+use itertools::Itertools;
+use serde_json::Value;
+use std::io::{self, Write};
+
+use super::ca::timeline::get_system_split;
+use super::table::{to_text, Table};
+
+//columns linked to issue pages
+const KEY_COLUMNS: [&str; 1] = ["key"];
+
+//columns which narrow the whole page down to a feature when clicked
+const FEATURE_COLUMNS: [&str; 2] = ["feature_id", "feature"];
+
+//columns of splits, whose rows belong to the system level split as well
+const SPLIT_COLUMNS: [&str; 1] = ["sub_id"];
+
+const STYLE: &str = "
+body { font-family: sans-serif; font-size: 13px; margin: 1em 2em; }
+h2 { margin-top: 1.5em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 2px 6px; text-align: left; vertical-align: top; }
+th { background: #eee; cursor: pointer; user-select: none; }
+th.asc::after { content: ' \\25B2'; }
+th.desc::after { content: ' \\25BC'; }
+tr:nth-child(even) td { background: #f8f8f8; }
+td.na { color: #999; }
+input.filter { margin: 4px 0; width: 20em; }
+#feature { display: none; padding: 4px 8px; background: #ffd; border: 1px solid #cc9; }
+";
+
+//sorting by clicked header, numbers by value, and filtering of rows by the
+// text of a table's box along with the feature drilled down to, which is one
+// of the features a row is tagged with; rows of tables without features
+// aren't tagged and always shown
+const SCRIPT: &str = "
+var feature = '';
+function matches(row, text) {
+  return row.textContent.toLowerCase().indexOf(text.toLowerCase()) >= 0;
+}
+function belongs(row, name) {
+  if (!name || row.dataset.feature === undefined) { return true; }
+  return JSON.parse(row.dataset.feature).indexOf(name) >= 0;
+}
+function refresh() {
+  document.querySelectorAll('section').forEach(function (section) {
+    var text = section.querySelector('input.filter').value;
+    var shown = 0;
+    section.querySelectorAll('tbody tr').forEach(function (row) {
+      var visible = matches(row, text) && belongs(row, feature);
+      row.style.display = visible ? '' : 'none';
+      if (visible) { shown++; }
+    });
+    section.querySelector('.shown').textContent = shown;
+  });
+  var banner = document.getElementById('feature');
+  banner.style.display = feature ? 'block' : 'none';
+  document.getElementById('feature-name').textContent = feature;
+}
+function drill(name) {
+  feature = name;
+  refresh();
+  return false;
+}
+function sort(th) {
+  var table = th.closest('table');
+  var body = table.tBodies[0];
+  var index = Array.prototype.indexOf.call(th.parentNode.children, th);
+  var asc = !th.classList.contains('asc');
+  table.querySelectorAll('th').forEach(function (it) { it.classList.remove('asc', 'desc'); });
+  th.classList.add(asc ? 'asc' : 'desc');
+  var rows = Array.prototype.slice.call(body.rows);
+  rows.sort(function (a, b) {
+    var x = a.cells[index].textContent, y = b.cells[index].textContent;
+    var order = (x !== '' && y !== '' && !isNaN(x) && !isNaN(y))
+      ? Number(x) - Number(y) : x.localeCompare(y);
+    return asc ? order : -order;
+  });
+  rows.forEach(function (row) { body.appendChild(row); });
+}
+";
+
+//self-contained html page of report tables, no external assets needed
+pub struct Dashboard<'a> {
+    title: String,
+
+    //issue pages are given by appending the key
+    browse_url: String,
+
+    sections: Vec<(String, &'a Table)>,
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for it in text.chars() {
+        match it {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&#39;"),
+            _ => output.push(it),
+        }
+    }
+    output
+}
+
+impl<'a> Dashboard<'a> {
+    pub fn new(title: &str, browse_url: &str) -> Dashboard<'a> {
+        Dashboard {
+            title: title.to_string(),
+            browse_url: browse_url.to_string(),
+            sections: Vec::new(),
+        }
+    }
+
+    pub fn section(mut self, heading: &str, table: &'a Table) -> Dashboard<'a> {
+        self.sections.push((heading.to_string(), table));
+        self
+    }
+
+    fn format_cell(&self, column: &str, value: &Value) -> String {
+        let text = escape(&to_text(value));
+        match value {
+            Value::Null => format!("<td class=\"na\">{}</td>", text),
+            _ if KEY_COLUMNS.contains(&column) => format!(
+                "<td><a href=\"{}{}\" target=\"_blank\">{}</a></td>",
+                escape(&self.browse_url),
+                text,
+                text
+            ),
+            _ if FEATURE_COLUMNS.contains(&column) => format!(
+                "<td><a href=\"#\" onclick=\"return drill(this.textContent)\">{}</a></td>",
+                text
+            ),
+            _ => format!("<td>{}</td>", text),
+        }
+    }
+
+    //features a row belongs to as json array, given by its feature columns or
+    // the system level split of its sub id
+    fn get_features(columns: &[&str], row: &[Value]) -> String {
+        let features = columns
+            .iter()
+            .zip(row)
+            .filter(|(_, value)| !value.is_null())
+            .filter_map(|(column, value)| {
+                let text = to_text(value);
+                if FEATURE_COLUMNS.contains(column) {
+                    Some(text)
+                } else if SPLIT_COLUMNS.contains(column) {
+                    Some(get_system_split(&text).to_string())
+                } else {
+                    None
+                }
+            })
+            .filter(|it| !it.is_empty())
+            .unique()
+            .collect::<Vec<String>>();
+        Value::from(features).to_string()
+    }
+
+    fn write_section<W: Write>(
+        &self,
+        writer: &mut W,
+        heading: &str,
+        table: &Table,
+    ) -> io::Result<()> {
+        writeln!(writer, "<section id=\"{}\">", escape(table.get_name()))?;
+        writeln!(
+            writer,
+            "<h2>{} (<span class=\"shown\">{}</span>/{})</h2>",
+            escape(heading),
+            table.len(),
+            table.len()
+        )?;
+        writeln!(
+            writer,
+            "<input class=\"filter\" placeholder=\"filter\" oninput=\"refresh()\">"
+        )?;
+        let columns = table.get_columns();
+        let header: String = columns
+            .iter()
+            .map(|it| format!("<th onclick=\"sort(this)\">{}</th>", escape(it)))
+            .collect();
+        writeln!(
+            writer,
+            "<table>\n<thead><tr>{}</tr></thead>\n<tbody>",
+            header
+        )?;
+        let tagged = columns
+            .iter()
+            .any(|it| FEATURE_COLUMNS.contains(it) || SPLIT_COLUMNS.contains(it));
+        for row in table.get_rows() {
+            let cells: String = columns
+                .iter()
+                .zip(row)
+                .map(|(column, value)| self.format_cell(column, value))
+                .collect();
+            let tag = if tagged {
+                let features = Self::get_features(&columns, row);
+                format!(" data-feature=\"{}\"", escape(&features))
+            } else {
+                String::new()
+            };
+            writeln!(writer, "<tr{}>{}</tr>", tag, cells)?;
+        }
+        writeln!(writer, "</tbody>\n</table>\n</section>")
+    }
+
+    pub fn write_html<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let title = escape(&self.title);
+        writeln!(
+            writer,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">"
+        )?;
+        writeln!(writer, "<title>{}</title>", title)?;
+        writeln!(writer, "<style>{}</style>\n</head>\n<body>", STYLE)?;
+        writeln!(writer, "<h1>{}</h1>", title)?;
+        let contents: Vec<String> = self
+            .sections
+            .iter()
+            .map(|(heading, table)| {
+                format!(
+                    "<a href=\"#{}\">{}</a>",
+                    escape(table.get_name()),
+                    escape(heading)
+                )
+            })
+            .collect();
+        writeln!(writer, "<nav>{}</nav>", contents.join(" | "))?;
+        writeln!(
+            writer,
+            "<p id=\"feature\">Feature <b id=\"feature-name\"></b> \
+             <a href=\"#\" onclick=\"return drill('')\">show all</a></p>"
+        )?;
+        for (heading, table) in &self.sections {
+            self.write_section(writer, heading, table)?;
+        }
+        writeln!(writer, "<script>{}</script>\n</body>\n</html>", SCRIPT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::table::cell;
+
+    #[test]
+    fn should_link_keys_and_features() {
+        let mut table = Table::new("ca-details-report")
+            .pad("feature_id", 9)
+            .pad("key", 10)
+            .pad("efforts", 4);
+        table.push(vec![cell(&"Fid-A"), cell(&"CA-1"), cell(&None::<u32>)]);
+        table.push(vec![cell(&"<b>"), cell(&"CA-2"), cell(&10)]);
+
+        let mut html = Vec::new();
+        Dashboard::new("CA", "https://jira.example.com/browse/")
+            .section("Details", &table)
+            .write_html(&mut html)
+            .unwrap();
+        let html = String::from_utf8(html).unwrap();
+        assert!(html.contains("<a href=\"https://jira.example.com/browse/CA-1\""));
+        assert!(html.contains("onclick=\"return drill(this.textContent)\">Fid-A</a>"));
+        assert!(html.contains("<td class=\"na\">NA</td>"));
+        assert!(html.contains("&lt;b&gt;"));
+        assert!(!html.contains("<b></a>"));
+        assert!(!html.contains("src=") && !html.contains("stylesheet"));
+        assert!(html.contains("<tr data-feature=\"[&quot;Fid-A&quot;]\"><td>"));
+        assert!(html.contains("<tr data-feature=\"[&quot;&lt;b&gt;&quot;]\">"));
+    }
+
+    //rows of a feature found by the tags which the page script compares
+    fn features_of(html: &str, section: &str) -> Vec<Vec<String>> {
+        let start = html.find(&format!("<section id=\"{}\">", section)).unwrap();
+        let end = start + html[start..].find("</section>").unwrap();
+        html[start..end]
+            .split("<tr data-feature=\"")
+            .skip(1)
+            .map(|it| {
+                let tags = it[..it.find('"').unwrap()].replace("&quot;", "\"");
+                serde_json::from_str(&tags).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_drill_into_feature_across_sys_and_ca() {
+        let mut sys = Table::new("sys-feature-details")
+            .hidden("feature")
+            .cut("summary", 20);
+        sys.push(vec![cell(&"Fid 1"), cell(&"Fid 1 | some feature")]);
+        sys.push(vec![cell(&"Other"), cell(&"Other feature")]);
+        let mut ca = Table::new("ca-details-report")
+            .pad("feature_id", 9)
+            .cut("sub_id", 15);
+        ca.push(vec![cell(&"Fid 1"), cell(&"Fid 1-A-a")]);
+        ca.push(vec![cell(&"Fid 10"), cell(&"Fid 10-A-a")]);
+        let mut html = Vec::new();
+        Dashboard::new("CA", "")
+            .section("System level features", &sys)
+            .section("CA details", &ca)
+            .write_html(&mut html)
+            .unwrap();
+        let html = String::from_utf8(html).unwrap();
+
+        let drilled = |section| -> usize {
+            features_of(&html, section)
+                .iter()
+                .filter(|tags| tags.iter().any(|it| it == "Fid 1"))
+                .count()
+        };
+        assert_eq!(drilled("sys-feature-details"), 1);
+        assert_eq!(drilled("ca-details-report"), 1);
+        assert_eq!(
+            features_of(&html, "ca-details-report")[0],
+            vec!["Fid 1", "Fid 1-A"]
+        );
+    }
+
+    #[test]
+    fn should_leave_tables_without_features_untagged() {
+        let mut table = Table::new("plain").pad("key", 10);
+        table.push(vec![cell(&"CA-1")]);
+        let mut html = Vec::new();
+        Dashboard::new("CA", "")
+            .section("Plain", &table)
+            .write_html(&mut html)
+            .unwrap();
+        assert!(String::from_utf8(html).unwrap().contains("<tr><td>"));
+    }
+
+    #[test]
+    fn should_tag_rows_by_feature_and_system_split() {
+        let columns = ["feature_id", "sub_id", "key"];
+        let row = vec![cell(&"Fid"), cell(&"Fid-A-a"), cell(&"CA-1")];
+        assert_eq!(
+            Dashboard::get_features(&columns, &row),
+            r#"["Fid","Fid-A"]"#
+        );
+        let row = vec![cell(&"Fid-A"), cell(&"Fid-A"), cell(&"CA-1")];
+        assert_eq!(Dashboard::get_features(&columns, &row), r#"["Fid-A"]"#);
+        let row = vec![cell(&None::<&str>), cell(&""), cell(&"CA-1")];
+        assert_eq!(Dashboard::get_features(&columns, &row), "[]");
+    }
+}
@@ -1,6 +1,6 @@
 pub trait ParsedData {
-    //get field lists
-    fn get_field_list() -> Vec<String>;
+    //get field lists, as keys of parsed fields with their default names
+    fn get_field_list() -> Vec<(&'static str, &'static str)>;
 }
 
 pub trait StoredData {
@@ -8,3 +8,16 @@ pub trait StoredData {
     //translate from parsed data type
     fn parse_from(parsed: &Self::Parsed) -> Self;
 }
+
+pub trait KeyedData {
+    //issue key, unique among items of a search
+    fn get_key(&self) -> &str;
+}
+
+pub trait LintedData {
+    //identifier shown along with problems
+    fn get_id(&self) -> &str;
+
+    //problems found in this item, empty if all good
+    fn lint(&self) -> Vec<String>;
+}
@@ -0,0 +1,229 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use super::datatypes::KeyedData;
+
+//a field of an item with different values in two snapshots
+#[derive(Serialize, Debug, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ItemChange {
+    pub key: String,
+    pub fields: Vec<FieldChange>,
+}
+
+//items of two snapshots matched by issue key, given as they're cached
+#[derive(Serialize, Debug, Default)]
+pub struct KeyedChanges {
+    pub added: Vec<Value>,
+    pub removed: Vec<Value>,
+    pub changed: Vec<ItemChange>,
+}
+
+//compare two snapshots by issue keys, changed items with their changed fields,
+// refused if some items have no key or share one, e.g. caches of old versions
+pub fn compare_by_key<T: Serialize + KeyedData>(
+    old: &[T],
+    new: &[T],
+) -> Result<KeyedChanges, String> {
+    let by_key = |items: &[T]| -> Result<BTreeMap<String, Value>, String> {
+        let mut keyed = BTreeMap::new();
+        for item in items {
+            let key = item.get_key();
+            if key.is_empty() {
+                return Err("items without key".to_string());
+            }
+            let value = serde_json::to_value(item).unwrap_or(Value::Null);
+            if keyed.insert(key.to_string(), value).is_some() {
+                return Err(format!("items of same key {}", key));
+            }
+        }
+        Ok(keyed)
+    };
+    let (mut old, new) = (by_key(old)?, by_key(new)?);
+
+    let mut changes = KeyedChanges::default();
+    for (key, after) in new {
+        match old.remove(&key) {
+            None => changes.added.push(after),
+            Some(before) => {
+                let fields = compare_fields(&before, &after);
+                if !fields.is_empty() {
+                    changes.changed.push(ItemChange { key, fields });
+                }
+            }
+        }
+    }
+    changes.removed = old.into_values().collect();
+    Ok(changes)
+}
+
+//different fields of two items, nested objects like fields of dynamic items
+// are compared by their own fields, named by dotted paths
+fn compare_fields(before: &Value, after: &Value) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    compare_values("", before, after, &mut changes);
+    changes
+}
+
+fn compare_values(path: &str, before: &Value, after: &Value, changes: &mut Vec<FieldChange>) {
+    let (old, new) = match (before.as_object(), after.as_object()) {
+        (Some(old), Some(new)) => (old, new),
+        _ => {
+            if before != after {
+                changes.push(FieldChange {
+                    field: path.to_string(),
+                    before: before.clone(),
+                    after: after.clone(),
+                });
+            }
+            return;
+        }
+    };
+    let mut names: Vec<&String> = old.keys().chain(new.keys()).collect();
+    names.sort();
+    names.dedup();
+    for name in names {
+        let field = if path.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", path, name)
+        };
+        compare_values(
+            &field,
+            old.get(name).unwrap_or(&Value::Null),
+            new.get(name).unwrap_or(&Value::Null),
+            changes,
+        );
+    }
+}
+
+impl KeyedChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    //one line per added or removed item, changed ones with a line per field
+    pub fn write_text<W: Write>(&self, writer: &mut W, old: &str, new: &str) -> io::Result<()> {
+        for item in &self.removed {
+            writeln!(writer, "- {}", item)?;
+        }
+        for item in &self.added {
+            writeln!(writer, "+ {}", item)?;
+        }
+        for item in &self.changed {
+            writeln!(writer, "~ {}", item.key)?;
+            for field in &item.fields {
+                writeln!(
+                    writer,
+                    "    {}: {} -> {}",
+                    field.field, field.before, field.after
+                )?;
+            }
+        }
+        writeln!(
+            writer,
+            "@@ {} -> {}: {} removed, {} added, {} changed",
+            old,
+            new,
+            self.removed.len(),
+            self.added.len(),
+            self.changed.len()
+        )
+    }
+
+    pub fn write_json<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *writer, self).map_err(io::Error::other)?;
+        writeln!(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Item {
+        key: &'static str,
+        start_fb: u32,
+        team: &'static str,
+    }
+
+    impl KeyedData for Item {
+        fn get_key(&self) -> &str {
+            self.key
+        }
+    }
+
+    fn item(key: &'static str, start_fb: u32, team: &'static str) -> Item {
+        Item {
+            key,
+            start_fb,
+            team,
+        }
+    }
+
+    #[test]
+    fn should_find_changed_fields_by_key() {
+        let old = vec![
+            item("A-1", 1901, "T1"),
+            item("A-2", 1902, "T1"),
+            item("A-3", 1903, "T1"),
+        ];
+        let new = vec![
+            item("A-4", 1904, "T2"),
+            item("A-2", 1905, "T2"),
+            item("A-3", 1903, "T1"),
+        ];
+        let changes = compare_by_key(&old, &new).unwrap();
+        assert_eq!(changes.removed.len(), 1);
+        assert_eq!(changes.removed[0]["key"], "A-1");
+        assert_eq!(changes.added[0]["key"], "A-4");
+        assert_eq!(changes.changed.len(), 1);
+        let fields: Vec<String> = changes.changed[0]
+            .fields
+            .iter()
+            .map(|it| format!("{}: {} -> {}", it.field, it.before, it.after))
+            .collect();
+        assert_eq!(
+            fields,
+            vec!["start_fb: 1902 -> 1905", r#"team: "T1" -> "T2""#]
+        );
+
+        let mut text = Vec::new();
+        changes.write_text(&mut text, "old", "new").unwrap();
+        let text = String::from_utf8(text).unwrap();
+        assert!(
+            text.contains("~ A-2\n    start_fb: 1902 -> 1905\n"),
+            "{}",
+            text
+        );
+        assert!(text.ends_with("@@ old -> new: 1 removed, 1 added, 1 changed\n"));
+
+        let mut json = Vec::new();
+        changes.write_json(&mut json).unwrap();
+        let json: Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json["changed"][0]["fields"][0]["after"], 1905);
+        assert!(compare_by_key(&new, &new).unwrap().is_empty());
+    }
+
+    #[test]
+    fn should_refuse_missing_or_duplicated_keys() {
+        let old = vec![item("A-1", 1901, "T1")];
+        let unkeyed = vec![item("", 1901, "T1")];
+        assert!(compare_by_key(&unkeyed, &old).is_err());
+        assert!(compare_by_key(&old, &unkeyed).is_err());
+        let duplicated = vec![item("A-1", 1901, "T1"), item("A-1", 1902, "T2")];
+        match compare_by_key(&old, &duplicated) {
+            Err(reason) => assert!(reason.contains("A-1"), "{}", reason),
+            Ok(_) => panic!("duplicated keys compared"),
+        }
+    }
+}
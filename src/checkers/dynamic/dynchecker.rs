@@ -0,0 +1,38 @@
+use super::dynitem::DynamicItem;
+use crate::checkers::table::{cell, Table};
+use crate::checkers::utils::{write_report, NO_ITEMS};
+use crate::config::SchemaConfig;
+use crate::error::Result;
+
+use std::io::Write;
+use std::path::Path;
+
+//list all items as a table of schema fields, named after the report
+pub fn analyze_results(
+    items: &[DynamicItem],
+    schema: &SchemaConfig,
+    name: &str,
+    out_dir: &Path,
+) -> Result<()> {
+    //columns are sized by content, no width needed, exported apart from the
+    // cache which may be named after the report too
+    let mut table = schema.fields.iter().fold(
+        Table::new(&format!("{}-report", name)).pad("key", 0),
+        |table, it| table.pad(&it.name, 0),
+    );
+    for item in items {
+        let mut row = vec![cell(&item.key)];
+        row.extend(schema.fields.iter().map(|it| item.get(&it.name).to_json()));
+        table.push(row);
+    }
+
+    info!("Got {} items of schema {}", items.len(), schema.name);
+    write_report(out_dir, &format!("{}.txt", name), |buf_writer| {
+        writeln!(buf_writer, "@@ {}: {} issues in total", name, items.len())?;
+        if items.is_empty() {
+            return buf_writer.write_all(NO_ITEMS.as_bytes());
+        }
+        buf_writer.write_all(table.format_sized().as_bytes())
+    })?;
+    table.export(out_dir)
+}
@@ -0,0 +1,134 @@
+use crate::checkers::fb::FeatureBuild;
+use crate::checkers::utils::*;
+use crate::config::{FieldKind, FieldSpec};
+use crate::query::issue::Issue;
+use serde_json::{Map, Value};
+
+use super::dynitem::FieldValue;
+
+//fields keyed by names they're declared by in the schema
+pub type DynamicFields = Map<String, Value>;
+
+pub type DynamicIssue = Issue<DynamicFields>;
+
+impl DynamicIssue {
+    pub fn get_value(&self, spec: &FieldSpec) -> FieldValue {
+        match self.fields.get(&spec.field) {
+            Some(value) => extract(value, spec.kind),
+            None => FieldValue::Missing,
+        }
+    }
+}
+
+//typed value of a field by its kind, missing if not given in that form
+pub fn extract(value: &Value, kind: FieldKind) -> FieldValue {
+    let text = |it: &str| {
+        let it = it.trim();
+        if it.is_empty() {
+            FieldValue::Missing
+        } else {
+            FieldValue::Text(it.to_string())
+        }
+    };
+    match kind {
+        FieldKind::String => text(get_wrapped_string(value, "")),
+        FieldKind::Option => text(get_wrapped_object_attr(value, "value")),
+        FieldKind::User => match get_wrapped_object_attr(value, "displayName") {
+            "" => text(get_wrapped_object_attr(value, "name")),
+            name => text(name),
+        },
+        FieldKind::Values => match value {
+            Value::Array(values) => FieldValue::List(
+                values
+                    .iter()
+                    .map(|it| match it {
+                        Value::String(it) => it.as_str(),
+                        _ => get_wrapped_object_attr(it, "value"),
+                    })
+                    .filter(|it| !it.is_empty())
+                    .map(String::from)
+                    .collect(),
+            ),
+            _ => FieldValue::Missing,
+        },
+        FieldKind::Number => match value.as_f64() {
+            Some(number) => FieldValue::Number(number),
+            None => FieldValue::Missing,
+        },
+        FieldKind::Hours => match value.as_f64() {
+            Some(seconds) => FieldValue::Number(seconds / 3600.0),
+            None => FieldValue::Missing,
+        },
+        FieldKind::Fb => {
+            let fb = match value {
+                Value::String(code) => FeatureBuild::parse(code),
+                Value::Number(code) => code.as_u64().map(|it| FeatureBuild::from_code(it as u32)),
+                _ => None,
+            };
+            fb.map_or(FieldValue::Missing, FieldValue::Fb)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extract_from(json: &str, kind: FieldKind) -> FieldValue {
+        extract(&serde_json::from_str(json).unwrap(), kind)
+    }
+
+    #[test]
+    fn should_extract_by_kind() {
+        let text = |it: &str| FieldValue::Text(it.to_string());
+        assert_eq!(
+            extract_from(r#"" Feature ""#, FieldKind::String),
+            text("Feature")
+        );
+        assert_eq!(
+            extract_from(r#"{"value": "EFS"}"#, FieldKind::Option),
+            text("EFS")
+        );
+        assert_eq!(
+            extract_from(
+                r#"{"name": "jdoe", "displayName": "John Doe"}"#,
+                FieldKind::User
+            ),
+            text("John Doe")
+        );
+        assert_eq!(
+            extract_from(
+                r#"[{"value": "rel1"}, {"value": ""}, "rel2"]"#,
+                FieldKind::Values
+            ),
+            FieldValue::List(vec!["rel1".to_string(), "rel2".to_string()])
+        );
+        assert_eq!(
+            extract_from("36000", FieldKind::Hours),
+            FieldValue::Number(10.0)
+        );
+        assert_eq!(
+            extract_from("60.5", FieldKind::Number),
+            FieldValue::Number(60.5)
+        );
+        assert_eq!(
+            extract_from(r#""1809""#, FieldKind::Fb),
+            FieldValue::Fb(FeatureBuild::from_code(1809))
+        );
+    }
+
+    #[test]
+    fn should_give_missing_for_null_or_other_forms() {
+        assert_eq!(extract_from("null", FieldKind::String), FieldValue::Missing);
+        assert_eq!(
+            extract_from(r#""""#, FieldKind::String),
+            FieldValue::Missing
+        );
+        assert_eq!(
+            extract_from(r#""EFS""#, FieldKind::Option),
+            FieldValue::Missing
+        );
+        assert_eq!(extract_from(r#""NA""#, FieldKind::Fb), FieldValue::Missing);
+        assert_eq!(extract_from("null", FieldKind::Values), FieldValue::Missing);
+    }
+}
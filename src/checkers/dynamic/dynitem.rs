@@ -0,0 +1,154 @@
+use super::super::datatypes::{KeyedData, LintedData};
+use super::dynissue::DynamicIssue;
+use crate::checkers::fb::FeatureBuild;
+use crate::checkers::utils::NA_STRING;
+use crate::config::SchemaConfig;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+
+//value of a field extracted by its declared kind
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, PartialOrd)]
+pub enum FieldValue {
+    Missing,
+    Text(String),
+    List(Vec<String>),
+    Number(f64),
+    Fb(FeatureBuild),
+}
+
+impl Display for FieldValue {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            FieldValue::Missing => write!(f, "{}", NA_STRING),
+            FieldValue::Text(text) => write!(f, "{}", text),
+            FieldValue::List(values) => write!(f, "{}", values.join(",")),
+            FieldValue::Number(number) => write!(f, "{}", number),
+            FieldValue::Fb(fb) => write!(f, "{}", fb),
+        }
+    }
+}
+
+impl FieldValue {
+    //plain value for exports, whole numbers without fraction as in reports
+    pub fn to_json(&self) -> Value {
+        match self {
+            FieldValue::Missing => Value::Null,
+            FieldValue::Text(text) => Value::from(text.as_str()),
+            FieldValue::List(values) => Value::from(values.clone()),
+            FieldValue::Number(number) if number.fract() == 0.0 && number.abs() < 1e15 => {
+                Value::from(*number as i64)
+            }
+            FieldValue::Number(number) => Value::from(*number),
+            FieldValue::Fb(fb) => Value::from(fb.get_code()),
+        }
+    }
+}
+
+//issue of a schema declared in config, values are keyed by their names
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DynamicItem {
+    pub key: String,
+    pub fields: BTreeMap<String, FieldValue>,
+}
+
+impl DynamicItem {
+    pub fn from(issue: &DynamicIssue, schema: &SchemaConfig) -> DynamicItem {
+        DynamicItem {
+            key: issue.key.clone(),
+            fields: schema
+                .fields
+                .iter()
+                .map(|spec| (spec.name.clone(), issue.get_value(spec)))
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> &FieldValue {
+        self.fields.get(name).unwrap_or(&FieldValue::Missing)
+    }
+}
+
+//values without a total order, i.e. NaN, are taken as equal
+impl Ord for DynamicItem {
+    fn cmp(&self, other: &DynamicItem) -> Ordering {
+        self.key.cmp(&other.key).then_with(|| {
+            self.fields
+                .partial_cmp(&other.fields)
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+}
+
+impl PartialOrd for DynamicItem {
+    fn partial_cmp(&self, other: &DynamicItem) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for DynamicItem {
+    fn eq(&self, other: &DynamicItem) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for DynamicItem {}
+
+impl KeyedData for DynamicItem {
+    fn get_key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl LintedData for DynamicItem {
+    fn get_id(&self) -> &str {
+        &self.key
+    }
+
+    fn lint(&self) -> Vec<String> {
+        self.fields
+            .iter()
+            .filter(|(_, value)| **value == FieldValue::Missing)
+            .map(|(name, _)| format!("no {}", name))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn should_convert_issue_by_schema() {
+        let config = Config::parse(
+            r#"
+            [[schema]]
+            name = "ca"
+            fields = [
+                { name = "feature_id", field = "Feature ID" },
+                { name = "start_fb", field = "Start FB", kind = "fb" },
+                { name = "team", field = "Team" },
+            ]
+        "#,
+        )
+        .unwrap();
+        let issue: DynamicIssue = serde_json::from_str(
+            r#"{"expand": "", "id": "1", "self": "", "key": "FPB-1",
+                "fields": {"Feature ID": "F1", "Start FB": "1809", "Team": null}}"#,
+        )
+        .unwrap();
+        let item = DynamicItem::from(&issue, &config.schemas[0]);
+        assert_eq!(item.get("feature_id").to_string(), "F1");
+        assert_eq!(
+            item.get("start_fb"),
+            &FieldValue::Fb(FeatureBuild::from_code(1809))
+        );
+        assert_eq!(item.get("unknown"), &FieldValue::Missing);
+        assert_eq!(item.lint(), vec!["no team"]);
+
+        let json = serde_json::to_string(&item).unwrap();
+        assert!(serde_json::from_str::<DynamicItem>(&json).unwrap() == item);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod dynchecker;
+pub mod dynissue;
+pub mod dynitem;
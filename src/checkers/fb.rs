@@ -0,0 +1,318 @@
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+use std::fmt::{self, Display, Formatter};
+
+//feature build, a planning period given by 2-digit year and build number of
+// the year like "1809", ordered by year first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FeatureBuild {
+    year: u32,
+    build: u32,
+}
+
+impl FeatureBuild {
+    pub const fn new(year: u32, build: u32) -> FeatureBuild {
+        FeatureBuild { year, build }
+    }
+
+    //given as number, e.g. 1809
+    pub fn from_code(code: u32) -> FeatureBuild {
+        FeatureBuild::new(code / 100, code % 100)
+    }
+
+    //given as text of 4 digits, None if it's not like "1809"
+    pub fn parse(value: &str) -> Option<FeatureBuild> {
+        let value = value.trim();
+        if value.len() != 4 || !value.chars().all(|it| it.is_ascii_digit()) {
+            return None;
+        }
+        value
+            .parse()
+            .ok()
+            .map(FeatureBuild::from_code)
+            .filter(|it| it.build > 0)
+    }
+
+    pub fn get_code(self) -> u32 {
+        self.year * 100 + self.build
+    }
+
+    pub fn get_year(self) -> u32 {
+        self.year
+    }
+
+    pub fn get_build(self) -> u32 {
+        self.build
+    }
+}
+
+impl Display for FeatureBuild {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.pad(&format!("{:02}{:02}", self.year, self.build))
+    }
+}
+
+//kept as number in caches, same as before it got its own type
+impl Serialize for FeatureBuild {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.get_code())
+    }
+}
+
+impl<'de> Deserialize<'de> for FeatureBuild {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u32::deserialize(deserializer).map(FeatureBuild::from_code)
+    }
+}
+
+//day of the gregorian calendar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: u32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    fn is_leap(year: u32) -> bool {
+        (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+    }
+
+    fn month_days(year: u32, month: u32) -> u32 {
+        match month {
+            2 if Date::is_leap(year) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    //given day of the year, starting from 0, later ones end up in last day
+    fn from_day_of_year(year: u32, days: u32) -> Date {
+        let mut days = days;
+        for month in 1..=12 {
+            let month_days = Date::month_days(year, month);
+            if days < month_days {
+                return Date {
+                    year,
+                    month,
+                    day: days + 1,
+                };
+            }
+            days -= month_days;
+        }
+        Date {
+            year,
+            month: 12,
+            day: 31,
+        }
+    }
+
+    //day of given seconds since 1970-01-01 UTC
+    pub fn from_timestamp(seconds: u64) -> Date {
+        let mut year = 1970;
+        let mut days = seconds / 86400;
+        loop {
+            let year_days = if Date::is_leap(year) { 366 } else { 365 };
+            if days < year_days {
+                return Date::from_day_of_year(year, days as u32);
+            }
+            days -= year_days;
+            year += 1;
+        }
+    }
+
+    //day of the year, starting from 0
+    fn day_of_year(self) -> u32 {
+        let before: u32 = (1..self.month)
+            .map(|it| Date::month_days(self.year, it))
+            .sum();
+        before + self.day - 1
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+//how builds divide a year, build 1 starts on January 1st and each one lasts
+// the same days, the last one of a year until its end
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct FbCalendar {
+    pub builds_per_year: u32,
+    pub build_days: u32,
+}
+
+impl Default for FbCalendar {
+    fn default() -> Self {
+        FbCalendar {
+            builds_per_year: 13,
+            build_days: 28,
+        }
+    }
+}
+
+impl FbCalendar {
+    //builds fit into a year and are given by 2 digits
+    pub fn validate(&self) -> Result<(), String> {
+        if self.builds_per_year == 0 || self.builds_per_year > 99 {
+            return Err(format!("{} builds per year", self.builds_per_year));
+        }
+        let days = self.builds_per_year.checked_mul(self.build_days);
+        if self.build_days == 0 || days.is_none_or(|it| it > 366) {
+            return Err(format!(
+                "{} builds of {} days don't fit into a year",
+                self.builds_per_year, self.build_days
+            ));
+        }
+        Ok(())
+    }
+
+    //a build of this calendar, e.g. not "1814" if there are 13 builds a year
+    pub fn is_valid(&self, fb: FeatureBuild) -> bool {
+        fb.build >= 1 && fb.build <= self.builds_per_year
+    }
+
+    fn index(&self, fb: FeatureBuild) -> i64 {
+        i64::from(fb.year) * i64::from(self.builds_per_year) + i64::from(fb.build) - 1
+    }
+
+    fn build_at(&self, index: i64) -> FeatureBuild {
+        let per_year = i64::from(self.builds_per_year);
+        let index = index.max(0);
+        FeatureBuild::new((index / per_year) as u32, (index % per_year) as u32 + 1)
+    }
+
+    //build given builds after, or before if negative
+    pub fn add(&self, fb: FeatureBuild, builds: i64) -> FeatureBuild {
+        self.build_at(self.index(fb) + builds)
+    }
+
+    //builds from one to another, negative if the other one is earlier
+    pub fn distance(&self, from: FeatureBuild, to: FeatureBuild) -> i64 {
+        self.index(to) - self.index(from)
+    }
+
+    //builds from start to end, both included
+    pub fn lead_time(&self, start: FeatureBuild, end: FeatureBuild) -> i64 {
+        self.distance(start, end) + 1
+    }
+
+    //all builds from start to end, both included
+    pub fn range(
+        &self,
+        start: FeatureBuild,
+        end: FeatureBuild,
+    ) -> impl Iterator<Item = FeatureBuild> {
+        let calendar = *self;
+        (self.index(start)..=self.index(end)).map(move |it| calendar.build_at(it))
+    }
+
+    pub fn start_date(&self, fb: FeatureBuild) -> Date {
+        let build = fb.build.clamp(1, self.builds_per_year);
+        Date::from_day_of_year(2000 + fb.year, (build - 1) * self.build_days)
+    }
+
+    //last day of the build
+    pub fn end_date(&self, fb: FeatureBuild) -> Date {
+        let year = 2000 + fb.year;
+        if fb.build >= self.builds_per_year {
+            return Date {
+                year,
+                month: 12,
+                day: 31,
+            };
+        }
+        let build = fb.build.max(1);
+        Date::from_day_of_year(year, build * self.build_days - 1)
+    }
+
+    //build of the day
+    pub fn build_of(&self, date: Date) -> FeatureBuild {
+        let build = (date.day_of_year() / self.build_days + 1).min(self.builds_per_year);
+        FeatureBuild::new(date.year % 100, build)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_and_order_builds() {
+        let fb = FeatureBuild::parse(" 1809").unwrap();
+        assert_eq!((fb.get_year(), fb.get_build()), (18, 9));
+        assert_eq!(format!("{:6}|", fb), "1809  |");
+        assert_eq!(FeatureBuild::parse("NA"), None);
+        assert_eq!(FeatureBuild::parse("180"), None);
+        assert_eq!(FeatureBuild::parse("1800"), None);
+        assert!(FeatureBuild::from_code(1813) < FeatureBuild::from_code(1901));
+        assert_eq!(serde_json::to_string(&fb).unwrap(), "1809");
+        assert_eq!(
+            serde_json::from_str::<FeatureBuild>("1901")
+                .unwrap()
+                .get_code(),
+            1901
+        );
+    }
+
+    #[test]
+    fn should_count_builds_across_years() {
+        let calendar = FbCalendar::default();
+        let (start, end) = (FeatureBuild::from_code(1808), FeatureBuild::from_code(1901));
+        assert_eq!(calendar.lead_time(start, end), 7);
+        assert_eq!(calendar.lead_time(start, FeatureBuild::from_code(2001)), 20);
+        assert_eq!(calendar.distance(end, start), -6);
+        assert_eq!(calendar.add(start, 6), end);
+        assert_eq!(calendar.add(end, -1).get_code(), 1813);
+        let codes: Vec<u32> = calendar
+            .range(FeatureBuild::from_code(1812), FeatureBuild::from_code(1902))
+            .map(FeatureBuild::get_code)
+            .collect();
+        assert_eq!(codes, vec![1812, 1813, 1901, 1902]);
+        assert!(!calendar.is_valid(FeatureBuild::from_code(1814)));
+
+        let monthly = FbCalendar {
+            builds_per_year: 12,
+            build_days: 30,
+        };
+        assert_eq!(monthly.lead_time(start, end), 6);
+        assert_eq!(monthly.add(end, -1).get_code(), 1812);
+    }
+
+    #[test]
+    fn should_map_builds_to_dates() {
+        let calendar = FbCalendar::default();
+        let fb = FeatureBuild::from_code(1902);
+        assert_eq!(calendar.start_date(fb).to_string(), "2019-01-29");
+        assert_eq!(calendar.end_date(fb).to_string(), "2019-02-25");
+        assert_eq!(
+            calendar.end_date(FeatureBuild::from_code(2013)).to_string(),
+            "2020-12-31"
+        );
+        assert_eq!(calendar.build_of(calendar.start_date(fb)), fb);
+        let new_year = Date {
+            year: 2020,
+            month: 12,
+            day: 31,
+        };
+        assert_eq!(calendar.build_of(new_year).get_code(), 2013);
+        assert_eq!(Date::from_timestamp(0).to_string(), "1970-01-01");
+        assert_eq!(Date::from_timestamp(1582934400).to_string(), "2020-02-29");
+        assert_eq!(Date::from_timestamp(1609459199).to_string(), "2020-12-31");
+        assert!(calendar.validate().is_ok());
+        let too_long = FbCalendar {
+            builds_per_year: 13,
+            build_days: 30,
+        };
+        assert!(too_long.validate().is_err());
+        let overflowing = FbCalendar {
+            builds_per_year: 13,
+            build_days: u32::MAX,
+        };
+        assert!(overflowing.validate().is_err());
+    }
+}
@@ -1,55 +1,85 @@
 use super::super::sys::sysitem::SysItem;
 use super::fs2item::Fs2Item;
-use crate::checkers::utils::get_leftmost;
+use crate::checkers::plan::Effort;
+use crate::checkers::table::{cell, Table};
+use crate::checkers::utils::{write_report, NO_ITEMS};
+use crate::error::Result;
 
 use std::fmt::format;
 use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
 
-pub fn analyze_results(result_list: &[Fs2Item], _sys_items: &[SysItem]) {
+pub fn analyze_results(
+    result_list: &[Fs2Item],
+    _sys_items: &[SysItem],
+    out_dir: &Path,
+) -> Result<()> {
     //dumping
+    let table = unsolved(result_list);
+    write_report(out_dir, "fs-analysis.txt", |buf_writer| {
+        dump_all(buf_writer, result_list, &table)
+    })?;
+    table.export(out_dir)?;
+    info!("Analyzed done for this analysis!\n");
+    Ok(())
+}
+
+//items not done or not estimated yet
+pub(crate) fn unsolved(result_list: &[Fs2Item]) -> Table {
+    let mut table = Table::new("fs-analysis")
+        .hidden("feature")
+        .cut("summary", 9)
+        .cut("title", 31)
+        .cut("release", 12)
+        .pad_right("efforts", 6)
+        .cut("description", 40);
+    for it in result_list
+        .iter()
+        .filter(|it| !it.has_efforts() || it.status != "Done")
+    {
+        table.push(vec![
+            cell(&it.get_fid()),
+            cell(&it.summary),
+            cell(&it.title),
+            cell(&it.release),
+            cell(&it.efforts),
+            cell(&it.description),
+        ]);
+    }
+    table
+}
+
+fn dump_all(
+    buf_writer: &mut BufWriter<File>,
+    result_list: &[Fs2Item],
+    unsolved: &Table,
+) -> io::Result<()> {
     let total = result_list.len();
-    let mut buf_writer = BufWriter::new(File::create("fs-analysis.txt").unwrap());
+    if total == 0 {
+        return buf_writer.write_all(NO_ITEMS.as_bytes());
+    }
     let banner = "----------------------------------------------------------------------------\n";
 
     //summarize
-    let unsolved: Vec<&Fs2Item> = result_list
-        .iter()
-        .filter(|it| !it.has_efforts() || it.status != "Done")
-        .collect();
     let summary_line = format(format_args!(
         "@@@ Total MZ FS2EE entries: {}, unresolved: {}\n",
         total,
         unsolved.len()
     ));
-    let _x = buf_writer.write_all(summary_line.as_bytes());
+    buf_writer.write_all(summary_line.as_bytes())?;
     info!("Got {} items for this analysis", total);
 
-    buf_writer.write_all(banner.as_bytes()).unwrap();
-    unsolved.iter().for_each(|it| {
-        let line = format(format_args!(
-            "{:9}|{:31}|{:12}|{:6}|{:40}\n",
-            get_leftmost(&it.summary, 9),
-            get_leftmost(&it.title, 31),
-            get_leftmost(&it.release, 12),
-            it.efforts,
-            get_leftmost(&it.description, 40)
-        ));
-        let _x = buf_writer.write_all(line.as_bytes());
-    });
-    buf_writer.write_all(banner.as_bytes()).unwrap();
-
-    let solved_eff: i32 = result_list
-        .iter()
-        .filter(|it| it.has_efforts())
-        .map(|it| it.efforts)
-        .sum();
+    buf_writer.write_all(banner.as_bytes())?;
+    unsolved.write_text(buf_writer)?;
+    buf_writer.write_all(banner.as_bytes())?;
+
+    let solved_eff: Effort = result_list.iter().filter_map(|it| it.efforts).sum();
 
     let line = format(format_args!(
         "@@@ Solved efforts are: {} with {} features\n",
         solved_eff,
         total - unsolved.len()
     ));
-    buf_writer.write_all(line.as_bytes()).unwrap();
-    info!("Analyzed done for this analysis!\n");
+    buf_writer.write_all(line.as_bytes())
 }
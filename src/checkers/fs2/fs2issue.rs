@@ -3,23 +3,21 @@ use super::super::utils::*;
 use crate::query::issue::Issue;
 use serde_json::Value;
 
-const FS2EE_FIELDS_SUMMARY: &str = "summary";
-const FS2EE_FIELDS_DESCRIPT: &str = "description";
-const FS2EE_FIELDS_STATUS: &str = "status";
-const FS2EE_FIELDS_TITLE: &str = "customfield_38703";
-const FS2EE_FIELDS_EE: &str = "customfield_38692";
-const FS2EE_FIELDS_RELEASE: &str = "customfield_38724";
+//keys of fields with their default names, overridden by [fields] of config
+const FS2EE_FIELDS_SUMMARY: (&str, &str) = ("summary", "summary");
+const FS2EE_FIELDS_DESCRIPT: (&str, &str) = ("description", "description");
+const FS2EE_FIELDS_STATUS: (&str, &str) = ("status", "status");
+const FS2EE_FIELDS_TITLE: (&str, &str) = ("title", "Title");
+const FS2EE_FIELDS_EE: (&str, &str) = ("fs2_efforts", "FS2 EE");
+const FS2EE_FIELDS_RELEASE: (&str, &str) = ("release", "Release");
 
 #[derive(Deserialize, Debug, Clone)]
 #[allow(non_snake_case)]
 pub struct Fs2Fields {
-    #[serde(rename = "customfield_38692")]
+    #[serde(rename = "fs2_efforts")]
     pub efforts: Value,
 
-    #[serde(rename = "customfield_38703")]
     pub title: Value,
-
-    #[serde(rename = "customfield_38724")]
     pub release: Value,
 
     pub summary: String,
@@ -74,7 +72,7 @@ impl Fs2Issue {
 
 impl ParsedData for Fs2Issue {
     //get field lists
-    fn get_field_list() -> Vec<String> {
+    fn get_field_list() -> Vec<(&'static str, &'static str)> {
         vec![
             FS2EE_FIELDS_SUMMARY,
             FS2EE_FIELDS_TITLE,
@@ -83,8 +81,5 @@ impl ParsedData for Fs2Issue {
             FS2EE_FIELDS_DESCRIPT,
             FS2EE_FIELDS_STATUS,
         ]
-        .iter()
-        .map(|x| x.to_string())
-        .collect()
     }
 }
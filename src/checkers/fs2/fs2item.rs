@@ -1,4 +1,5 @@
-use super::super::datatypes::StoredData;
+use super::super::datatypes::{KeyedData, LintedData, StoredData};
+use super::super::plan::{estimated, Effort};
 use super::fs2issue::Fs2Issue;
 use std::cmp::Ord;
 use std::cmp::Ordering;
@@ -6,11 +7,17 @@ use std::cmp::Ordering;
 #[derive(Serialize, Deserialize, Clone, Debug, Eq)]
 pub struct Fs2Item {
     pub summary: String,
-    pub efforts: i32,
+    //none if not estimated
+    #[serde(default, deserialize_with = "estimated")]
+    pub efforts: Option<Effort>,
     pub title: String,
     pub release: String,
     pub description: String,
     pub status: String,
+
+    //not given by caches of older versions
+    #[serde(default)]
+    pub key: String,
 }
 
 impl Fs2Item {
@@ -18,15 +25,24 @@ impl Fs2Item {
         Fs2Item {
             summary: raw.fields.summary.clone(),
             title: raw.get_title_display(),
-            efforts: raw.get_efforts().map(|x| x as i32).unwrap_or(-1),
+            efforts: raw.get_efforts(),
             release: raw.get_release(),
             description: raw.get_description(),
             status: raw.get_status().clone(),
+            key: raw.key.clone(),
         }
     }
 
+    //feature estimated, e.g. "5GC001000" of "5GC001000-EE-MANO MZ"
+    pub fn get_fid(&self) -> &str {
+        self.summary
+            .split(['-', ' '])
+            .next()
+            .unwrap_or(&self.summary)
+    }
+
     pub fn has_efforts(&self) -> bool {
-        self.efforts != -1
+        self.efforts.is_some()
     }
 }
 
@@ -51,6 +67,12 @@ impl PartialEq for Fs2Item {
     }
 }
 
+impl KeyedData for Fs2Item {
+    fn get_key(&self) -> &str {
+        &self.key
+    }
+}
+
 impl StoredData for Fs2Item {
     type Parsed = Fs2Issue;
 
@@ -58,3 +80,20 @@ impl StoredData for Fs2Item {
         Self::from(issue)
     }
 }
+
+impl LintedData for Fs2Item {
+    fn get_id(&self) -> &str {
+        &self.summary
+    }
+
+    fn lint(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if !self.has_efforts() {
+            problems.push("not estimated".to_string());
+        }
+        if self.release.is_empty() {
+            problems.push("no release".to_string());
+        }
+        problems
+    }
+}
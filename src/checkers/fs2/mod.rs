@@ -24,9 +24,9 @@ mod test {
                         "summary":"5GC001000-EE-MANO MZ",
                         "status" : {"name": "Done"},
                         "description": "some desc",
-                        "customfield_38724" : [{"value":"rel1"}],
-                        "customfield_38692":60.0,
-                        "customfield_38703":"20 MHz cell bandwidth for cmWave"
+                        "release" : [{"value":"rel1"}],
+                        "fs2_efforts":60.0,
+                        "title":"20 MHz cell bandwidth for cmWave"
                     }
                 },
                 {
@@ -38,9 +38,9 @@ mod test {
                         "summary":"5GC0010xxx-EE-MANO MZ",
                         "status" : {"name": "Done"},
                         "description": "some desc",
-                        "customfield_38724" : [{"value":"rel1"}],
-                        "customfield_38692":null,
-                        "customfield_38703":"What ever in fp"
+                        "release" : [{"value":"rel1"}],
+                        "fs2_efforts":null,
+                        "title":"What ever in fp"
                     }
                 },
                 {
@@ -50,11 +50,11 @@ mod test {
                     "key" : "FPB-12447",
                     "fields" : {
                         "summary":"5GC0010xxx-EE-MANO MZ",
-                        "customfield_38692":0.0,
+                        "fs2_efforts":0.0,
                         "status" : {"name": "Done"},
                         "description": "some desc",
-                        "customfield_38724" : [{"value":"rel1"}],
-                        "customfield_38703":"What ever in fp"
+                        "release" : [{"value":"rel1"}],
+                        "title":"What ever in fp"
                     }
                 }
             ]
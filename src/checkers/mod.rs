@@ -1,11 +1,21 @@
+pub mod adhoc;
 pub mod analyze;
 pub mod ca;
+pub mod dashboard;
 pub(crate) mod datatypes;
+pub mod diff;
+pub mod dynamic;
+pub mod fb;
 pub mod fs2;
 pub(crate) mod persist;
+pub mod plan;
 pub(crate) mod records;
+pub mod runner;
 pub(crate) mod search;
+pub mod store;
+pub(crate) mod sync;
 pub mod sys;
+pub mod table;
 pub(crate) mod utils;
 
 #[cfg(test)]
@@ -11,7 +11,8 @@ pub(crate) enum ParseError {
     Outdated,
 }
 
-pub(crate) fn parse_from<T, R>(reader: R) -> Result<Records<T>, ParseError>
+//parse records, outdated ones are only rejected if asked to
+pub(crate) fn parse_from<T, R>(reader: R, check_outdated: bool) -> Result<Records<T>, ParseError>
 where
     R: Read,
     T: DeserializeOwned,
@@ -24,7 +25,7 @@ where
                 .duration_since(SystemTime::UNIX_EPOCH)
                 .unwrap()
                 .as_secs();
-            if current_time - REFRESH_THRESHOLD >= rc.timestamp {
+            if check_outdated && current_time - REFRESH_THRESHOLD >= rc.timestamp {
                 warn!(
                     "Local cache is outdated, would refresh from server! Saved: {}, now {}",
                     rc.timestamp, current_time
@@ -41,13 +42,12 @@ where
     }
 }
 
-//write given items into output with current timestamp
-pub(crate) fn write_to<T, W>(writer: W, items: Vec<T>) -> (Result<String, String>, Vec<T>)
+//write given records into output
+pub(crate) fn write_to<T, W>(writer: W, rec: Records<T>) -> (Result<String, String>, Vec<T>)
 where
     W: Write,
     T: Serialize,
 {
-    let rec = Records::new(items);
     let result = serde_json::to_writer_pretty(writer, &rec)
         .map(|_| {
             info!(
@@ -65,12 +65,13 @@ where
 mod tests {
     use super::*;
     use crate::checkers::ca::caitem::{Activity, CAItem};
+    use crate::checkers::fb::FeatureBuild;
     use std::time::SystemTime;
     type ParseResult = Result<Records<CAItem>, ParseError>;
 
     #[test]
     fn should_parse_records_from_json() {
-        let result: ParseResult = parse_from(get_test_data(0).as_bytes());
+        let result: ParseResult = parse_from(get_test_data(0).as_bytes(), true);
         assert!(
             result.is_ok(),
             "Parse failed by:{:?}",
@@ -85,15 +86,15 @@ mod tests {
         assert_eq!(item.sub_id, "SomeFeature-A-a");
         assert_eq!(item.description, "some desc");
         assert_eq!(item.feature_id, "SomeFeature");
-        assert_eq!(item.start_fb, 1809);
-        assert_eq!(item.end_fb, 1809);
+        assert_eq!(item.start_fb.map(FeatureBuild::get_code), Some(1809));
+        assert_eq!(item.end_fb.map(FeatureBuild::get_code), Some(1809));
         assert_eq!(item.activity, Activity::SW);
     }
 
     #[test]
     fn should_discard_parsed_if_older_than_2_hours() {
         let json = get_test_data(REFRESH_THRESHOLD);
-        let result: ParseResult = parse_from(json.as_bytes());
+        let result: ParseResult = parse_from(json.as_bytes(), true);
         assert!(result.is_err());
         let error = result.err().unwrap();
         match error {
@@ -130,13 +131,20 @@ mod tests {
         }"#
     }
 
+    #[test]
+    fn should_keep_outdated_if_not_checked() {
+        let json = get_test_data(REFRESH_THRESHOLD);
+        let result: ParseResult = parse_from(json.as_bytes(), false);
+        assert!(result.is_ok());
+    }
+
     #[test]
     fn should_forward_json_parsing_error() {
         let json = r#"{
             "timestamp": "invalid type!",
             "records": []
         }"#;
-        let result: ParseResult = parse_from(json.as_bytes());
+        let result: ParseResult = parse_from(json.as_bytes(), true);
         assert!(result.is_err());
         match result.err().unwrap() {
             ParseError::Json(_) => {}
@@ -150,20 +158,21 @@ mod tests {
     fn should_save_items_without_err() {
         let json = r#"{"expand" : "", "id": "", "self": "", "key": "", "fields": {
                 "summary":"Leading - something else",
-                "customfield_37381":"Feature_ID",
-                "customfield_38727":"Team yyy",
-                "customfield_38694":"1808",
-                "customfield_38693":"1809",
-                "customfield_38723":"PT4",
-                "timeoriginalestimate":360000,
-                "customfield_38750":{ "value": "EFS"}
+                "feature_id":"Feature_ID",
+                "team":"Team yyy",
+                "start_fb":"1808",
+                "end_fb":"1809",
+                "target":"PT4",
+                "original_estimate":360000,
+                "activity_type":{ "value": "EFS"}
         }}"#;
         let issue = serde_json::from_str(&json);
         let item = CAItem::from(&issue.unwrap());
         let items = vec![item];
 
         let storage: Vec<u8> = Vec::new();
-        let result: (Result<String, String>, Vec<CAItem>) = write_to(storage, items);
+        let result: (Result<String, String>, Vec<CAItem>) =
+            write_to(storage, Records::synced("", 0, items));
         assert!(result.0.is_ok());
     }
 }
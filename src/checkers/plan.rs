@@ -0,0 +1,65 @@
+use serde::de::{Deserialize, Deserializer};
+
+use super::fb::FeatureBuild;
+
+//estimated efforts in hours
+pub type Effort = u32;
+
+//caches of older versions gave an unplanned build as 9999
+const LEGACY_UNPLANNED: u32 = 9999;
+
+//build of a plan, either a number or null, unplanned ones of older caches too
+pub(crate) fn planned_fb<'de, D>(deserializer: D) -> Result<Option<FeatureBuild>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let code = Option::<u32>::deserialize(deserializer)?;
+    Ok(code
+        .filter(|it| *it != LEGACY_UNPLANNED)
+        .map(FeatureBuild::from_code)
+        .filter(|it| it.get_build() > 0))
+}
+
+//efforts, either a number or null, unestimated ones of older caches were -1
+pub(crate) fn estimated<'de, D>(deserializer: D) -> Result<Option<Effort>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let efforts = Option::<i64>::deserialize(deserializer)?;
+    Ok(efforts.filter(|it| *it >= 0).map(|it| it as Effort))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Plan {
+        #[serde(deserialize_with = "planned_fb")]
+        start_fb: Option<FeatureBuild>,
+        #[serde(deserialize_with = "estimated")]
+        efforts: Option<Effort>,
+    }
+
+    fn parse(json: &str) -> (Option<u32>, Option<Effort>) {
+        let plan: Plan = serde_json::from_str(json).unwrap();
+        (plan.start_fb.map(FeatureBuild::get_code), plan.efforts)
+    }
+
+    #[test]
+    fn should_migrate_legacy_sentinels() {
+        assert_eq!(
+            parse(r#"{"start_fb": 1809, "efforts": 10}"#),
+            (Some(1809), Some(10))
+        );
+        assert_eq!(parse(r#"{"start_fb": 9999, "efforts": -1}"#), (None, None));
+        assert_eq!(
+            parse(r#"{"start_fb": null, "efforts": null}"#),
+            (None, None)
+        );
+        assert_eq!(
+            parse(r#"{"start_fb": 1800, "efforts": 0}"#),
+            (None, Some(0))
+        );
+    }
+}
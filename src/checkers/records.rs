@@ -5,21 +5,32 @@ use self::serde::de::Deserialize;
 
 #[derive(Deserialize, Serialize, Clone)]
 pub(crate) struct Records<T> {
+    //when items were synced from the server, in seconds since epoch
     pub timestamp: u64,
 
+    //jql of the items, empty for caches of older versions
+    #[serde(default)]
+    pub query: String,
+
     #[serde(bound(deserialize = "T:Deserialize<'de>"))]
     pub records: Vec<T>,
 }
 
 use std::time::SystemTime;
 impl<T> Records<T> {
-    pub fn new(records: Vec<T>) -> Records<T> {
+    pub fn synced(query: &str, timestamp: u64, records: Vec<T>) -> Records<T> {
         Records {
-            timestamp: SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            timestamp,
+            query: query.to_string(),
             records,
         }
     }
 }
+
+//current time in seconds since epoch
+pub(crate) fn get_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
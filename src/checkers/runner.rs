@@ -0,0 +1,841 @@
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use tokio_core::reactor::Core;
+
+use super::analyze::{load_cache, load_items, load_records, load_with, CachePolicy, Source};
+use super::ca::{cachecker, caitem::CAItem};
+use super::datatypes::{KeyedData, LintedData};
+use super::diff::compare_by_key;
+use super::dynamic::dynchecker;
+use super::dynamic::dynissue::DynamicIssue;
+use super::dynamic::dynitem::DynamicItem;
+use super::fs2::{fs2checker, fs2item::Fs2Item};
+use super::records::{get_timestamp, Records};
+use super::store::{Revision, Store};
+use super::sys::{syschecker, sysitem::SysItem};
+use super::utils::write_report;
+use crate::config::{CheckerKind, Config, ConfigError, ReportConfig};
+use crate::error::{Error, Result};
+use crate::fetch::fetcher::Fetcher;
+
+pub struct RunOptions {
+    //cache policy for selected reports, their inputs are reused if possible
+    pub policy: CachePolicy,
+
+    //generate report files or only load items
+    pub render: bool,
+
+    //overrides output directory of every report
+    pub output_dir: Option<String>,
+
+    //report names to run, all if empty
+    pub selected: Vec<String>,
+}
+
+//items collected by finished reports, keyed by report name
+#[derive(Default)]
+struct Collected {
+    sys: HashMap<String, Vec<SysItem>>,
+    fs2: HashMap<String, Vec<Fs2Item>>,
+}
+
+impl Collected {
+    fn sys_input(&self, report: &ReportConfig) -> &[SysItem] {
+        Collected::find_input(&self.sys, report)
+    }
+
+    fn fs2_input(&self, report: &ReportConfig) -> &[Fs2Item] {
+        Collected::find_input(&self.fs2, report)
+    }
+
+    //inputs are validated by config to be one of each kind, so a missing one
+    // is simply empty
+    fn find_input<'a, T>(items: &'a HashMap<String, Vec<T>>, report: &ReportConfig) -> &'a [T] {
+        report
+            .inputs
+            .iter()
+            .filter_map(|name| items.get(name))
+            .next()
+            .map_or(&[], |it| it.as_slice())
+    }
+}
+
+//run selected reports with all their inputs in dependency order
+pub fn run_reports(
+    config: &Config,
+    core: &mut Core,
+    mut fetcher: Option<&mut Fetcher>,
+    options: &RunOptions,
+) -> Result<()> {
+    let reports = config.ordered_reports()?;
+    if reports.is_empty() {
+        warn!("No report configured, nothing to do!");
+    }
+    let selected = select_reports(config, &options.selected)?;
+    let needed = with_all_inputs(config, &selected);
+
+    let mut collected = Collected::default();
+    for report in reports
+        .into_iter()
+        .filter(|it| needed.contains(it.name.as_str()))
+    {
+        let is_selected = selected.contains(report.name.as_str());
+        let policy = match options.policy {
+            CachePolicy::Refresh | CachePolicy::Sync if !is_selected => CachePolicy::Reuse,
+            policy => policy,
+        };
+        let render = options.render && is_selected;
+        info!(
+            "Running report {} by {:?} checker, {:?}",
+            report.name, report.checker, policy
+        );
+
+        let out_dir = Path::new(options.output_dir.as_ref().unwrap_or(&report.output_dir));
+        if render {
+            fs::create_dir_all(out_dir).map_err(|source| Error::Report {
+                path: out_dir.display().to_string(),
+                source,
+            })?;
+        }
+
+        let (jql, cache) = (report.jql.as_str(), report.cache.as_str());
+        let fetcher = fetcher.as_deref_mut();
+        match report.checker {
+            CheckerKind::Sys => {
+                let items = load_tracked(report, out_dir, || {
+                    load_items(config, core, fetcher, jql, cache, policy)
+                })?;
+                if render {
+                    syschecker::analyze_results(&items, out_dir)?;
+                }
+                collected.sys.insert(report.name.clone(), items);
+            }
+            CheckerKind::Fs2 => {
+                let items = load_tracked(report, out_dir, || {
+                    load_items(config, core, fetcher, jql, cache, policy)
+                })?;
+                if render {
+                    fs2checker::analyze_results(&items, collected.sys_input(report), out_dir)?;
+                }
+                collected.fs2.insert(report.name.clone(), items);
+            }
+            CheckerKind::Ca => {
+                let items: Vec<CAItem> = load_tracked(report, out_dir, || {
+                    load_items(config, core, fetcher, jql, cache, policy)
+                })?;
+                if render {
+                    let (sys_items, fs2_items) =
+                        (collected.sys_input(report), collected.fs2_input(report));
+                    //as of the snapshot analyzed, the same one for its replays
+                    let synced = load_records::<IgnoredAny>(cache)
+                        .map_or_else(get_timestamp, |it| it.timestamp);
+                    cachecker::analyze_result(
+                        &items,
+                        sys_items,
+                        fs2_items,
+                        &config.calendar,
+                        synced,
+                        &config.jira.get_browse_url(),
+                        out_dir,
+                    )?;
+                }
+            }
+            CheckerKind::Dynamic => {
+                let schema = config.get_schema(report)?;
+                let source = Source {
+                    search: jql,
+                    fields: schema
+                        .fields
+                        .iter()
+                        .map(|it| (it.field.clone(), it.field.clone()))
+                        .collect(),
+                    cache_fname: cache,
+                };
+                let items: Vec<DynamicItem> = load_tracked(report, out_dir, || {
+                    load_with(
+                        config,
+                        core,
+                        fetcher,
+                        &source,
+                        policy,
+                        |it: &DynamicIssue| DynamicItem::from(it, schema),
+                    )
+                })?;
+                if render {
+                    dynchecker::analyze_results(&items, schema, &report.name, out_dir)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+//check cached items of selected reports, giving number of problems found
+pub fn lint_reports<W: Write>(
+    config: &Config,
+    selected: &[String],
+    writer: &mut W,
+) -> Result<usize> {
+    let selected = select_reports(config, selected)?;
+    let mut total = 0;
+    for report in config
+        .reports
+        .iter()
+        .filter(|it| selected.contains(it.name.as_str()))
+    {
+        writeln!(writer, "@@ {} ({})", report.name, report.cache).map_err(output_error)?;
+        let found = match report.checker {
+            CheckerKind::Sys => lint_cache::<SysItem, W>(&report.cache, writer),
+            CheckerKind::Fs2 => lint_cache::<Fs2Item, W>(&report.cache, writer),
+            CheckerKind::Ca => lint_cache::<CAItem, W>(&report.cache, writer),
+            CheckerKind::Dynamic => lint_cache::<DynamicItem, W>(&report.cache, writer),
+        };
+        total += found.map_err(output_error)?;
+    }
+    Ok(total)
+}
+
+fn lint_cache<T, W>(cache: &str, writer: &mut W) -> io::Result<usize>
+where
+    T: DeserializeOwned + LintedData,
+    W: Write,
+{
+    let items: Vec<T> = match load_cache(cache, false) {
+        Some(items) => items,
+        None => {
+            writeln!(writer, "no usable cache, skipped")?;
+            return Ok(0);
+        }
+    };
+
+    let mut found = 0;
+    for item in &items {
+        for problem in item.lint() {
+            writeln!(writer, "{:12}|{}", item.get_id(), problem)?;
+            found += 1;
+        }
+    }
+    writeln!(writer, "{} problems in {} items", found, items.len())?;
+    Ok(found)
+}
+
+//compare an older snapshot of given report with a newer one, or its current cache
+pub fn diff_report<W: Write>(
+    config: &Config,
+    name: &str,
+    old: &str,
+    new: Option<&str>,
+    json: bool,
+    writer: &mut W,
+) -> Result<()> {
+    let report = config
+        .find_report(name)
+        .ok_or_else(|| ConfigError::Invalid(format!("unknown report {}", name)))?;
+    let new = new.unwrap_or(&report.cache);
+    match report.checker {
+        CheckerKind::Sys => diff_caches::<SysItem, W>(old, new, json, writer),
+        CheckerKind::Fs2 => diff_caches::<Fs2Item, W>(old, new, json, writer),
+        CheckerKind::Ca => diff_caches::<CAItem, W>(old, new, json, writer),
+        CheckerKind::Dynamic => diff_caches::<DynamicItem, W>(old, new, json, writer),
+    }
+}
+
+fn diff_caches<T, W>(old: &str, new: &str, json: bool, writer: &mut W) -> Result<()>
+where
+    T: DeserializeOwned + Serialize + KeyedData,
+    W: Write,
+{
+    let load = |cache: &str| -> Result<Vec<T>> {
+        load_cache(cache, false).ok_or_else(|| Error::Cache {
+            path: cache.to_string(),
+            source: io::Error::new(io::ErrorKind::InvalidData, "no usable records"),
+        })
+    };
+    let changes = compare_by_key(&load(old)?, &load(new)?).map_err(|reason| Error::Cache {
+        path: format!("{}, {}", old, new),
+        source: io::Error::new(io::ErrorKind::InvalidData, reason),
+    })?;
+    if json {
+        changes.write_json(writer)
+    } else {
+        changes.write_text(writer, old, new)
+    }
+    .map_err(output_error)
+}
+
+//items loaded by given function, and if the cache is written again by it,
+// their changes since the previous cache written next to the other reports
+fn load_tracked<T, F>(report: &ReportConfig, out_dir: &Path, load: F) -> Result<Vec<T>>
+where
+    T: DeserializeOwned + Serialize + KeyedData,
+    F: FnOnce() -> Result<Vec<T>>,
+{
+    let previous: Option<Records<T>> = load_records(&report.cache);
+    let items = load()?;
+    let previous = match previous {
+        Some(previous) => previous,
+        None => return Ok(items),
+    };
+    let synced = load_records::<IgnoredAny>(&report.cache).map(|it| it.timestamp);
+    let changes = match compare_by_key(&previous.records, &items) {
+        Ok(changes) => changes,
+        Err(reason) => {
+            warn!("No changes of {} given: {}", report.name, reason);
+            return Ok(items);
+        }
+    };
+    //cache reused, changes of the run fetching it are kept, a cache written
+    // within the same second is told apart by its changes
+    if synced == Some(previous.timestamp) && changes.is_empty() {
+        return Ok(items);
+    }
+    info!(
+        "{} items added, {} removed, {} changed in {}",
+        changes.added.len(),
+        changes.removed.len(),
+        changes.changed.len(),
+        report.name
+    );
+    let since = format!("synced at {}", previous.timestamp);
+    fs::create_dir_all(out_dir).map_err(|source| Error::Report {
+        path: out_dir.display().to_string(),
+        source,
+    })?;
+    write_report(out_dir, &format!("{}-changes.txt", report.name), |writer| {
+        changes.write_text(writer, &since, "now")
+    })?;
+    write_report(
+        out_dir,
+        &format!("{}-changes.json", report.name),
+        |writer| changes.write_json(writer),
+    )?;
+    Ok(items)
+}
+
+//states of an item of given report kept by the history database, only the
+// one in effect given days ago if asked
+pub fn history_report<W: Write>(
+    config: &Config,
+    name: &str,
+    key: &str,
+    days_ago: Option<u64>,
+    writer: &mut W,
+) -> Result<()> {
+    let report = config
+        .find_report(name)
+        .ok_or_else(|| ConfigError::Invalid(format!("unknown report {}", name)))?;
+    let database = config.history.database.as_ref().ok_or_else(|| {
+        ConfigError::Invalid("no history database configured in [history]".to_string())
+    })?;
+    let store = Store::open(database)?;
+    let until = days_ago.map(|days| get_timestamp().saturating_sub(days.saturating_mul(86_400)));
+    match report.checker {
+        CheckerKind::Sys => write_history::<SysItem, W>(&store, report, key, until, writer),
+        CheckerKind::Fs2 => write_history::<Fs2Item, W>(&store, report, key, until, writer),
+        CheckerKind::Ca => write_history::<CAItem, W>(&store, report, key, until, writer),
+        CheckerKind::Dynamic => write_history::<DynamicItem, W>(&store, report, key, until, writer),
+    }
+}
+
+fn write_history<T, W>(
+    store: &Store,
+    report: &ReportConfig,
+    key: &str,
+    until: Option<u64>,
+    writer: &mut W,
+) -> Result<()>
+where
+    T: DeserializeOwned + Serialize,
+    W: Write,
+{
+    let mut revisions: Vec<Revision<T>> = store.history(&report.cache, key)?;
+    if let Some(until) = until {
+        revisions.retain(|it| it.synced <= until);
+        revisions = revisions.pop().into_iter().collect();
+    }
+    for revision in &revisions {
+        let json = serde_json::to_string(&revision.item).map_err(|e| output_error(e.into()))?;
+        writeln!(writer, "@@ synced at {}\n{}", revision.synced, json).map_err(output_error)?;
+    }
+    if revisions.is_empty() {
+        writeln!(writer, "@@ {} not found in history of {}", key, report.name)
+            .map_err(output_error)?;
+    }
+    Ok(())
+}
+
+//failure of writing into the output given by caller, normally stdout
+fn output_error(source: io::Error) -> Error {
+    Error::Report {
+        path: "<output>".to_string(),
+        source,
+    }
+}
+
+//names of given reports, or all reports if none given
+fn select_reports<'a>(
+    config: &'a Config,
+    names: &'a [String],
+) -> std::result::Result<HashSet<&'a str>, ConfigError> {
+    if names.is_empty() {
+        return Ok(config.reports.iter().map(|it| it.name.as_str()).collect());
+    }
+    for name in names {
+        if config.find_report(name).is_none() {
+            return Err(ConfigError::Invalid(format!("unknown report {}", name)));
+        }
+    }
+    Ok(names.iter().map(|it| it.as_str()).collect())
+}
+
+fn with_all_inputs<'a>(config: &'a Config, selected: &HashSet<&'a str>) -> HashSet<&'a str> {
+    let mut needed: HashSet<&str> = HashSet::new();
+    let mut pending: Vec<&str> = selected.iter().cloned().collect();
+    while let Some(name) = pending.pop() {
+        if !needed.insert(name) {
+            continue;
+        }
+        if let Some(report) = config.find_report(name) {
+            pending.extend(report.inputs.iter().map(|it| it.as_str()));
+        }
+    }
+    needed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::fb::FeatureBuild;
+    use crate::fetch::auth::Auth;
+    use crate::fetch::cassette::Cassette;
+    use crate::mock::{issue, MockJira};
+    use std::path::PathBuf;
+
+    #[test]
+    fn should_select_reports_with_inputs() {
+        let config = Config::parse(
+            r#"
+            [[report]]
+            name = "sys"
+            checker = "sys"
+            jql = ""
+            cache = "sys.json"
+
+            [[report]]
+            name = "fs2"
+            checker = "fs2"
+            jql = ""
+            cache = "fs2.json"
+            inputs = ["sys"]
+
+            [[report]]
+            name = "other-sys"
+            checker = "sys"
+            jql = ""
+            cache = "other.json"
+        "#,
+        )
+        .unwrap();
+
+        let names = vec!["fs2".to_string()];
+        let selected = select_reports(&config, &names).unwrap();
+        let needed = with_all_inputs(&config, &selected);
+        assert_eq!(needed.len(), 2);
+        assert!(needed.contains("sys") && needed.contains("fs2"));
+
+        assert_eq!(select_reports(&config, &[]).unwrap().len(), 3);
+        assert!(select_reports(&config, &["ca".to_string()]).is_err());
+    }
+
+    const FIELDS: &str = r#"{
+            "summary": "Feature-A-a: some desc",
+            "description": "some desc",
+            "status": {"name": "Done"},
+            "customfield_37381": "Feature",
+            "customfield_38692": 10,
+            "customfield_38693": "1812",
+            "customfield_38694": "1809",
+            "customfield_38703": "Some title",
+            "customfield_38711": "OAM",
+            "customfield_38723": "PT4",
+            "customfield_38724": [{"value": "rel1"}],
+            "customfield_38727": "Team",
+            "customfield_38750": {"value": "EFS"},
+            "timeoriginalestimate": 36000
+        }"#;
+
+    fn start_server(total: usize) -> MockJira {
+        let issues = (0..total)
+            .map(|it| {
+                issue(
+                    &format!("FPB-{}", it),
+                    serde_json::from_str(FIELDS).unwrap(),
+                )
+            })
+            .collect();
+        let server = MockJira::start(issues);
+        for (id, name) in &[
+            ("customfield_37381", "Feature ID"),
+            ("customfield_38692", "FS2 EE"),
+            ("customfield_38693", "End FB"),
+            ("customfield_38694", "Start FB"),
+            ("customfield_38703", "Title"),
+            ("customfield_38711", "Area"),
+            ("customfield_38723", "Target"),
+            ("customfield_38724", "Release"),
+            ("customfield_38727", "Team"),
+            ("customfield_38750", "Activity Type"),
+        ] {
+            server.add_field(id, name);
+        }
+        server
+    }
+
+    //all kinds of reports against given server, cached in given directory
+    fn reports_config(server: &MockJira, dir: &Path) -> Config {
+        let cache = |name: &str| dir.join(name).display().to_string();
+        let mut config = server.config();
+        let reports = Config::parse(&format!(
+            r#"
+            [[report]]
+            name = "sys"
+            checker = "sys"
+            jql = "project = SYS"
+            cache = "{}"
+
+            [[report]]
+            name = "fs2"
+            checker = "fs2"
+            jql = "project = FS2"
+            cache = "{}"
+            inputs = ["sys"]
+
+            [[report]]
+            name = "ca"
+            checker = "ca"
+            jql = "project = CA"
+            cache = "{}"
+            inputs = ["sys", "fs2"]
+
+            [[report]]
+            name = "features"
+            checker = "dynamic"
+            jql = "project = FPB"
+            cache = "{}"
+            schema = "feature"
+
+            [[schema]]
+            name = "feature"
+            fields = [
+                {{ name = "feature_id", field = "Feature ID" }},
+                {{ name = "start_fb", field = "Start FB", kind = "fb" }},
+                {{ name = "activity", field = "Activity Type", kind = "option" }},
+                {{ name = "releases", field = "Release", kind = "values" }},
+                {{ name = "efforts", field = "timeoriginalestimate", kind = "hours" }},
+            ]
+        "#,
+            cache("sys.json"),
+            cache("fs2.json"),
+            cache("ca.json"),
+            cache("features.json")
+        ))
+        .unwrap();
+        config.schemas = reports.schemas;
+        config.reports = reports.reports;
+        config
+    }
+
+    fn run_all(config: &Config, dir: &Path) -> Result<()> {
+        run_with(config, dir, CachePolicy::Refresh)
+    }
+
+    fn run_with(config: &Config, dir: &Path, policy: CachePolicy) -> Result<()> {
+        let fetcher = Fetcher::new(Auth::bearer("token"), config).unwrap();
+        run_by(config, dir, policy, fetcher)
+    }
+
+    fn run_by(
+        config: &Config,
+        dir: &Path,
+        policy: CachePolicy,
+        mut fetcher: Fetcher,
+    ) -> Result<()> {
+        let mut core = Core::new().unwrap();
+        let options = RunOptions {
+            policy,
+            render: true,
+            output_dir: Some(dir.display().to_string()),
+            selected: Vec::new(),
+        };
+        run_reports(config, &mut core, Some(&mut fetcher), &options)
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("jira-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn should_run_all_reports_from_server() {
+        let server = start_server(120);
+        let dir = temp_dir("runner");
+        let cache = |name: &str| dir.join(name).display().to_string();
+        run_all(&reports_config(&server, &dir), &dir).unwrap();
+
+        //fields found by their default names in the catalogue
+        let sys: Vec<SysItem> = load_cache(&cache("sys.json"), true).unwrap();
+        assert_eq!(sys.len(), 120);
+        assert_eq!(
+            (sys[0].area.as_str(), sys[0].title.as_str()),
+            ("OAM", "Some title")
+        );
+        assert_eq!(sys[0].release, "rel1");
+        let fs2: Vec<Fs2Item> = load_cache(&cache("fs2.json"), true).unwrap();
+        assert_eq!(fs2[0].efforts, Some(10));
+        let ca: Vec<CAItem> = load_cache(&cache("ca.json"), true).unwrap();
+        assert_eq!(
+            (ca[0].feature_id.as_str(), ca[0].team.as_str()),
+            ("Feature", "Team")
+        );
+        assert_eq!(ca[0].start_fb, Some(FeatureBuild::from_code(1809)));
+        assert_eq!(ca[0].end_fb, Some(FeatureBuild::from_code(1812)));
+        assert_eq!((ca[0].target.as_str(), ca[0].efforts), ("PT4", Some(10)));
+        let features: Vec<DynamicItem> = load_cache(&cache("features.json"), true).unwrap();
+        assert_eq!(features[0].get("efforts").to_string(), "10");
+        assert_eq!(features[0].get("releases").to_string(), "rel1");
+        assert_eq!(features[0].get("activity").to_string(), "EFS");
+        let rows: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.join("features-report.json")).unwrap())
+                .unwrap();
+        assert_eq!(rows[0]["key"], "FPB-0");
+        assert_eq!(rows[0]["efforts"], 10);
+        for report in &[
+            "features.txt",
+            "sys-feature-details.txt",
+            "fs-analysis.txt",
+            "ca-pipeline.txt",
+            "features-report.csv",
+            "sys-feature-details.csv",
+            "fs-analysis.json",
+            "ca-details-report.json",
+            "ca-lead-time.csv",
+            "ca-lead-time-top.json",
+            "ca-plan-status.csv",
+            "ca-pipeline.json",
+        ] {
+            assert!(dir.join(report).exists(), "{} not generated", report);
+        }
+        let dashboard = fs::read_to_string(dir.join("ca-dashboard.html")).unwrap();
+        assert!(dashboard.contains("/browse/FPB-0\""));
+        let gantt = fs::read_to_string(dir.join("ca-gantt.html")).unwrap();
+        assert!(gantt.contains("<svg") && gantt.contains("</svg>"));
+        //4 reports with 2 pages each, by fields resolved once
+        assert_eq!(server.get_searches().len(), 8);
+        assert_eq!(server.get_catalog_requests(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn should_replay_as_of_recording() {
+        let server = start_server(10);
+        let dir = temp_dir("replay");
+        let path = dir.join("cassette.jsonl");
+        let config = reports_config(&server, &dir);
+        let cassette = Cassette::record(&path).unwrap();
+        let recorded = cassette.get_timestamp().unwrap();
+        let fetcher = Fetcher::new(Auth::bearer("token"), &config).unwrap();
+        run_by(
+            &config,
+            &dir,
+            CachePolicy::Refresh,
+            fetcher.with_cassette(cassette),
+        )
+        .unwrap();
+
+        //recorded in september 2018, among the builds of the pipeline
+        let cassette = fs::read_to_string(&path)
+            .unwrap()
+            .replace(&format!("\"time\":{}", recorded), "\"time\":1536796800");
+        fs::write(&path, cassette).unwrap();
+        let replay = || {
+            let fetcher = Fetcher::new(Auth::Anonymous, &config).unwrap();
+            let cassette = Cassette::replay(&path).unwrap();
+            run_by(
+                &config,
+                &dir,
+                CachePolicy::Refresh,
+                fetcher.with_cassette(cassette),
+            )
+            .unwrap();
+            fs::read_to_string(dir.join("ca-gantt.html")).unwrap()
+        };
+        let gantt = replay();
+        let ca = load_records::<IgnoredAny>(&dir.join("ca.json").display().to_string());
+        assert_eq!(ca.unwrap().timestamp, 1536796800);
+        assert!(gantt.contains(">now</text>"));
+        assert_eq!(replay(), gantt);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn should_resolve_configured_fields_by_saved_catalog() {
+        let server = start_server(3);
+        let dir = temp_dir("fields");
+        let mut config = reports_config(&server, &dir);
+        config
+            .fields
+            .insert("area".to_string(), "customfield_38703".to_string());
+        run_all(&config, &dir).unwrap();
+        let sys: Vec<SysItem> =
+            load_cache(&dir.join("sys.json").display().to_string(), true).unwrap();
+        assert_eq!(sys[0].area, "Some title");
+        assert_eq!(sys[0].title, "Some title");
+        assert!(dir.join("field-catalog.json").exists());
+
+        //saved catalogue is reused by other runs unless refreshing
+        run_with(&config, &dir, CachePolicy::Sync).unwrap();
+        assert_eq!(server.get_catalog_requests(), 1);
+        run_all(&config, &dir).unwrap();
+        assert_eq!(server.get_catalog_requests(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn should_sync_updated_and_removed_issues() {
+        let server = start_server(5);
+        let dir = temp_dir("sync");
+        let mut config = reports_config(&server, &dir);
+        config.history.database = Some(dir.join("history.db").display().to_string());
+        //nothing synced yet, so all are fetched
+        run_with(&config, &dir, CachePolicy::Sync).unwrap();
+        assert_eq!(server.get_searches().len(), 4);
+
+        server.remove_issue("FPB-1");
+        let mut fields: serde_json::Value = serde_json::from_str(FIELDS).unwrap();
+        fields["customfield_37381"] = "Changed".into();
+        fields["customfield_38694"] = "1901".into();
+        server.update_issue("FPB-2", fields.clone());
+        server.update_issue("FPB-7", fields);
+        run_with(&config, &dir, CachePolicy::Sync).unwrap();
+
+        //keys and updated issues for each report
+        let queries = server.get_queries();
+        assert_eq!(queries.len(), 12);
+        assert!(queries[4..].iter().any(|it| it.contains("updated >=")));
+        let features: Vec<DynamicItem> =
+            load_cache(&dir.join("features.json").display().to_string(), true).unwrap();
+        let keys: Vec<&str> = features.iter().map(|it| it.key.as_str()).collect();
+        assert_eq!(keys, vec!["FPB-0", "FPB-2", "FPB-3", "FPB-4", "FPB-7"]);
+        assert_eq!(features[1].get("feature_id").to_string(), "Changed");
+        assert_eq!(features[1].get("start_fb").to_string(), "1901");
+        assert_eq!(features[2].get("feature_id").to_string(), "Feature");
+
+        let changes = fs::read_to_string(dir.join("features-changes.txt")).unwrap();
+        assert!(changes.contains("~ FPB-2\n"), "{}", changes);
+        assert!(changes.contains(r#"fields.feature_id.Text: "Feature" -> "Changed""#));
+        assert!(changes.ends_with("now: 1 removed, 1 added, 1 changed\n"));
+        assert!(dir.join("ca-changes.json").exists());
+
+        //both snapshots kept, unchanged items only once
+        let mut history = Vec::new();
+        history_report(&config, "features", "FPB-2", None, &mut history).unwrap();
+        let history = String::from_utf8(history).unwrap();
+        assert_eq!(history.matches("@@ synced at").count(), 2, "{}", history);
+        assert!(history.contains("Changed"));
+        let mut history = Vec::new();
+        history_report(&config, "features", "FPB-3", None, &mut history).unwrap();
+        assert_eq!(String::from_utf8(history).unwrap().lines().count(), 2);
+        //too many days ago is just before any snapshot
+        let mut history = Vec::new();
+        history_report(&config, "features", "FPB-3", Some(u64::MAX), &mut history).unwrap();
+        assert_eq!(
+            String::from_utf8(history).unwrap(),
+            "@@ FPB-3 not found in history of features\n"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn should_give_changes_whenever_cache_is_rewritten() {
+        let server = start_server(3);
+        let dir = temp_dir("changes");
+        let config = reports_config(&server, &dir);
+        run_all(&config, &dir).unwrap();
+        assert!(!dir.join("sys-changes.txt").exists());
+
+        //fetched without rendering, e.g. by fetch command
+        server.remove_issue("FPB-1");
+        let mut core = Core::new().unwrap();
+        let mut fetcher = Fetcher::new(Auth::bearer("token"), &config).unwrap();
+        let options = RunOptions {
+            policy: CachePolicy::Refresh,
+            render: false,
+            output_dir: Some(dir.display().to_string()),
+            selected: Vec::new(),
+        };
+        run_reports(&config, &mut core, Some(&mut fetcher), &options).unwrap();
+        let changes = fs::read_to_string(dir.join("sys-changes.txt")).unwrap();
+        assert!(
+            changes.ends_with("1 removed, 0 added, 0 changed\n"),
+            "{}",
+            changes
+        );
+
+        //reused cache keeps changes of the run fetching it
+        run_with(&config, &dir, CachePolicy::Reuse).unwrap();
+        let changes = fs::read_to_string(dir.join("sys-changes.txt")).unwrap();
+        assert!(
+            changes.ends_with("1 removed, 0 added, 0 changed\n"),
+            "{}",
+            changes
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn should_give_no_items_reports_for_empty_search() {
+        let server = start_server(0);
+        let dir = temp_dir("empty");
+        run_all(&reports_config(&server, &dir), &dir).unwrap();
+
+        let cached: Vec<CAItem> = load_cache(&dir.join("ca.json").display().to_string(), true)
+            .expect("empty result should be cached");
+        assert!(cached.is_empty());
+        for report in &[
+            "features.txt",
+            "sys-feature-details.txt",
+            "fs-analysis.txt",
+            "ca-details-report.txt",
+            "ca-pipeline.txt",
+        ] {
+            let content = fs::read_to_string(dir.join(report)).unwrap();
+            assert!(
+                content.contains("No items found"),
+                "{}: {}",
+                report,
+                content
+            );
+        }
+        assert_eq!(server.get_searches().len(), 4);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn should_give_no_report_for_failed_search() {
+        let server = start_server(10);
+        server.fail_page(0, 401, 1);
+        let dir = temp_dir("failed");
+        match run_all(&reports_config(&server, &dir), &dir) {
+            Err(Error::Denied { .. }) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(!dir.join("sys.json").exists());
+        assert!(!dir.join("sys-feature-details.txt").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
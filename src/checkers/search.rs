@@ -1,23 +1,38 @@
+use std::cell::RefCell;
 use std::clone::Clone;
-use std::sync::mpsc::channel;
-use std::sync::mpsc::Receiver;
-use std::sync::mpsc::Sender;
-use tokio_core::reactor::Core;
+use std::time::Duration;
+use tokio_core::reactor::{Core, Handle, Timeout};
 
-use hyper::StatusCode;
 use serde::de::DeserializeOwned;
 
-use crate::fetch::fetcher::{Fetcher, RequestInfo};
+use crate::config::SearchConfig;
+use crate::error::{Error, PageFailure, Result};
+use crate::fetch::fetcher::{Fetcher, RequestInfo, Response};
+use crate::fetch::retry::{get_retry_after, is_retryable, RetryPolicy};
+use crate::fetch::throttle::TokenBucket;
 use crate::query::batch::Query;
+use crate::query::field::FieldMap;
 use crate::query::result::{parse_query_result, QueryResult};
-use futures::future::{join_all, Future};
+use futures::future::{err, loop_fn, ok, Either, Future, Loop};
+use futures::stream::{self, Stream};
+
+//what to do after an attempt of a page
+enum Outcome<T> {
+    Done(Box<QueryResult<T>>),
+    Retry(Error, Option<Duration>),
+    Fail(Error),
+}
+
+type PageResult<T> = std::result::Result<Box<QueryResult<T>>, PageFailure>;
 
 pub struct Searcher<'a> {
     core: &'a mut Core,
     fetcher: &'a mut Fetcher,
     uri: &'a str,
-    pending_jobs: Vec<Query>,
-    finished: Vec<usize>,
+    retry: RetryPolicy,
+    page_size: usize,
+    max_in_flight: usize,
+    throttle: RefCell<TokenBucket>,
 }
 
 impl<'a> Searcher<'a> {
@@ -25,133 +40,348 @@ impl<'a> Searcher<'a> {
         core: &'a mut Core,
         fetcher: &'a mut Fetcher,
         uri: &'a str,
-        pending: Vec<Query>,
+        config: &SearchConfig,
     ) -> Searcher<'a> {
         Searcher {
             core,
             fetcher,
             uri,
-            pending_jobs: pending,
-            finished: Vec::new(),
+            retry: RetryPolicy::from_config(config),
+            page_size: config.page_size.max(1),
+            max_in_flight: config.max_in_flight.max(1),
+            throttle: RefCell::new(TokenBucket::new(
+                config.requests_per_second,
+                config.max_in_flight,
+            )),
         }
     }
 
     //Search by given jql and issue fields, and collect all results in one single
     // result, 2-phases based search is used to calculating paging properly.
+    // Issue fields are given by their declared names.
     pub fn perform<T: DeserializeOwned>(
         &mut self,
         jql: &str,
-        fields: Vec<String>,
+        fields: &FieldMap,
         result: &mut QueryResult<T>,
-    ) {
-        let search = Query::new(jql.to_string(), 100, fields);
+    ) -> Result<()> {
+        self.fetcher.authenticate(self.core)?;
+        let search = Query::new(jql.to_string(), self.page_size, fields.get_ids());
         //first search
-        self.reset_pending(vec![search.clone()])
-            .perform_parallel(result);
-        if result.issues.is_empty() {
-            error!("First search failed?");
-            panic!("Unexpected ending!");
+        let paging = match self.fetch_pages(vec![search.clone()], fields).pop() {
+            Some(Ok(first)) => {
+                let paging = first.get_paging();
+                result.collect_from(*first);
+                paging
+            }
+            Some(Err(failure)) => {
+                error!("First search failed: {}", failure.error);
+                return Err(failure.error);
+            }
+            None => unreachable!(),
+        };
+        //an empty result is a success, unlike a failed first page
+        if paging.total == 0 {
+            info!("Nothing found by this search!");
+            return Ok(());
         }
 
         //remaining
         info!("Got first result now, check remaining by page info!");
-        self.reset_pending(search.create_remaining(result.total))
-            .perform_parallel(result);
-    }
+        if paging.max_results < self.page_size {
+            info!(
+                "Server caps page size to {} instead of {}",
+                paging.max_results, self.page_size
+            );
+        }
+        let pages = search.create_remaining(paging);
+        let total = pages.len();
+        let mut failures = Vec::new();
+        for (x, page) in self.fetch_pages(pages, fields).into_iter().enumerate() {
+            match page {
+                Ok(qry) => {
+                    result.collect_from(*qry);
+                    info!("[{}/{}] Collected a paged response!", x + 1, total);
+                }
+                Err(failure) => failures.push(failure),
+            }
+        }
 
-    fn reset_pending(&mut self, new_pending: Vec<Query>) -> &mut Searcher<'a> {
-        self.pending_jobs = new_pending;
-        self
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            error!("{} of {} pages failed permanently!", failures.len(), total);
+            Err(Error::Pages(failures))
+        }
     }
 
-    fn perform_parallel<T: DeserializeOwned>(&mut self, result: &mut QueryResult<T>) {
-        let (tx, rx) = channel();
+    //fetch given pages with at most max_in_flight requests at the same time,
+    // each one retried on its own, results are in the same order as pages
+    fn fetch_pages<T: DeserializeOwned>(
+        &mut self,
+        pages: Vec<Query>,
+        fields: &FieldMap,
+    ) -> Vec<PageResult<T>> {
         debug!(
-            "There're {} jobs to perform query!",
-            self.pending_jobs.len()
+            "There're {} jobs to perform query, {} at a time!",
+            pages.len(),
+            self.max_in_flight
         );
-        while !self.pending_jobs.is_empty() {
-            debug!(
-                "There're {} jobs to perform query!",
-                self.pending_jobs.len()
-            );
-            self.finished.clear();
-            self.drain_all_jobs(&tx);
-            self.collect_all_responses(result, &rx);
-            self.clean_finished_from_pending();
+        let handle = self.core.handle();
+        let (fetcher, uri, retry): (&Fetcher, _, _) = (self.fetcher, self.uri, &self.retry);
+        let throttle = &self.throttle;
+        let sub_queries = stream::iter_ok(pages.iter())
+            .map(|qry| fetch_page(fetcher, &handle, uri, qry, fields, retry, throttle))
+            .buffered(self.max_in_flight)
+            .collect();
+
+        debug!("Run all sub-queries!");
+        //page futures never fail, failures are given as results
+        match self.core.run(sub_queries) {
+            Ok(results) => results,
+            Err(()) => unreachable!(),
         }
     }
+}
 
-    fn drain_all_jobs<T: DeserializeOwned>(
-        &mut self,
-        sender: &Sender<Result<Box<QueryResult<T>>, usize>>,
-    ) {
-        let mut sub_queries = Vec::new();
-        //Drain all pending jobs
-        for qry in self.pending_jobs.iter() {
-            //query this page
-            let sender1 = sender.clone();
-            let parser = move |json: &str, code: StatusCode| {
-                let my_result = match code {
-                    StatusCode::OK => parse_query_result::<T>(&json).ok_or_else(|| qry.startAt),
-                    _ => Err(qry.startAt),
-                };
-                let _x = sender1.send(my_result);
+//fetch a single page, retry by given policy
+fn fetch_page<'f, T: DeserializeOwned + 'f>(
+    fetcher: &'f Fetcher,
+    handle: &'f Handle,
+    uri: &'f str,
+    qry: &'f Query,
+    fields: &'f FieldMap,
+    retry: &'f RetryPolicy,
+    throttle: &'f RefCell<TokenBucket>,
+) -> impl Future<Item = PageResult<T>, Error = ()> + 'f {
+    let body = qry.to_json().expect("Query is always serializable");
+    loop_fn(1, move |attempt| {
+        let post_info = RequestInfo::post(uri, &body);
+        let send = move || match post_info {
+            Ok(post_info) => Either::A(fetcher.query_with(post_info)),
+            Err(e) => Either::B(err(e)),
+        };
+        //every attempt, retries included, waits for its turn by rate limit
+        let wait = throttle.borrow_mut().reserve();
+        let request = if wait == Duration::from_secs(0) {
+            Either::A(send())
+        } else {
+            debug!("Page {} waits {:?} by rate limit", qry.startAt, wait);
+            let timer = Timeout::new(wait, handle).expect("Unable to create timer!");
+            Either::B(timer.then(move |_| send()))
+        };
+        request.then(move |response| {
+            let outcome = match response {
+                Ok(response) => check_response(response, fields),
+                Err(err @ Error::Network(_)) => Outcome::Retry(err, None),
+                Err(err) => Outcome::Fail(err),
+            };
+            let failure = |error: Error| PageFailure {
+                start_at: qry.startAt,
+                attempts: attempt,
+                error,
+            };
+
+            let next: Box<dyn Future<Item = _, Error = ()>> = match outcome {
+                Outcome::Done(page) => Box::new(ok(Loop::Break(Ok(page)))),
+                Outcome::Retry(error, retry_after) if retry.can_retry(attempt) => {
+                    let delay = retry.delay_for(attempt, retry_after);
+                    warn!(
+                        "Page {} attempt {} failed by {}, retry in {:?}",
+                        qry.startAt, attempt, error, delay
+                    );
+                    let timer = Timeout::new(delay, handle).expect("Unable to create timer!");
+                    Box::new(timer.then(move |_| Ok(Loop::Continue(attempt + 1))))
+                }
+                Outcome::Retry(error, _) | Outcome::Fail(error) => {
+                    warn!("This job {} has failed by {}", qry.startAt, error);
+                    Box::new(ok(Loop::Break(Err(failure(error)))))
+                }
             };
+            next
+        })
+    })
+}
 
-            let post_info = RequestInfo::post(self.uri, &qry.to_json().unwrap());
-            let guard1 = sender.clone();
-            let sub_fetch = self
-                .fetcher
-                .query_with(post_info, self.core, Some(parser))
-                .map_err(move |err| {
-                    //TODO: handle exceptions in graceful manner?
-                    warn!("This job {} has failed by {}", qry.startAt, err);
-                    let _x = guard1.send(Err(qry.startAt + 10000));
-                    "failed"
-                });
-            sub_queries.push(sub_fetch);
+fn check_response<T: DeserializeOwned>(response: Response, fields: &FieldMap) -> Outcome<T> {
+    if response.status.is_success() {
+        match parse_query_result::<T>(&fields.rename_page(&response.body)) {
+            Ok(page) => Outcome::Done(page),
+            Err(err) => Outcome::Retry(err, None),
+        }
+    } else {
+        let retry_after = get_retry_after(response.status, &response.headers);
+        let err = Error::from_response(response.status, &response.headers, &response.body);
+        //auth and jql errors never succeed by retrying
+        if is_retryable(response.status) {
+            Outcome::Retry(err, retry_after)
+        } else {
+            Outcome::Fail(err)
         }
-        debug!("Run all sub-queries!");
-        let _x = self.core.run(join_all(sub_queries));
     }
+}
 
-    fn collect_all_responses<T: DeserializeOwned>(
-        &mut self,
-        result: &mut QueryResult<T>,
-        receiver: &Receiver<Result<Box<QueryResult<T>>, usize>>,
-    ) {
-        let total = self.pending_jobs.len();
-        for x in 1..=total {
-            if let Ok(process_result) = receiver.recv() {
-                match process_result {
-                    Ok(qry) => {
-                        self.finished.push(qry.startAt);
-                        result.collect_from(*qry);
-                        info!("[{}/{}] Collected a paged response!", x, total);
-                    }
-                    Err(job) => {
-                        if job > 10000 {
-                            //Should also know which was not so successful since upon failure
-                            // unsuccessful futures might have been cancelled
-                            //job -= 10000;
-                            break;
-                        } else {
-                            info!("[{}/{}] unexpected response, would retry this", x, total);
-                        }
-                    }
-                }
-            } else {
-                error!("recev error now!");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::fetch::auth::Auth;
+    use crate::fetch::cassette::Cassette;
+    use crate::mock::{issue, MockJira};
+    use crate::query::issue::Issue;
+    use serde_json::{Map, Value};
+
+    type Fields = Map<String, Value>;
+
+    fn start_server(total: usize) -> MockJira {
+        let issues = (0..total)
+            .map(|it| {
+                let fields = serde_json::from_str(&format!(
+                    r#"{{"summary": "issue {}", "status": {{"name": "Done"}}}}"#,
+                    it
+                ))
+                .unwrap();
+                issue(&format!("FPB-{}", it), fields)
+            })
+            .collect();
+        MockJira::start(issues)
+    }
+
+    fn search(config: &Config, fields: &[&str]) -> (Result<()>, QueryResult<Issue<Fields>>) {
+        let fetcher = Fetcher::new(Auth::bearer("token"), config).unwrap();
+        search_by(fetcher, config, fields)
+    }
+
+    fn search_by(
+        mut fetcher: Fetcher,
+        config: &Config,
+        fields: &[&str],
+    ) -> (Result<()>, QueryResult<Issue<Fields>>) {
+        let mut core = Core::new().unwrap();
+        let uri = config.search_uri();
+        let mut result = QueryResult::default(100);
+        let done = Searcher::new(&mut core, &mut fetcher, &uri, &config.search).perform(
+            "project = FPB",
+            &FieldMap::by_ids(fields),
+            &mut result,
+        );
+        (done, result)
+    }
+
+    #[test]
+    fn should_collect_all_pages() {
+        let server = start_server(250);
+        let (done, result) = search(&server.config(), &["summary"]);
+        done.unwrap();
+        assert_eq!(result.issues.len(), 250);
+        assert_eq!(result.issues[249].key, "FPB-249");
+        assert_eq!(result.issues[0].fields.len(), 1);
+
+        let mut searches = server.get_searches();
+        searches.sort();
+        assert_eq!(searches, vec![0, 100, 200]);
+    }
+
+    #[test]
+    fn should_follow_page_size_of_server() {
+        let server = start_server(120);
+        server.set_max_results(50);
+        let (done, result) = search(&server.config(), &["summary"]);
+        done.unwrap();
+        assert_eq!(result.issues.len(), 120);
+        let mut searches = server.get_searches();
+        searches.sort();
+        assert_eq!(searches, vec![0, 50, 100]);
+    }
+
+    #[test]
+    fn should_search_single_short_page() {
+        let server = start_server(7);
+        let (done, result) = search(&server.config(), &["summary"]);
+        done.unwrap();
+        assert_eq!(result.issues.len(), 7);
+        assert_eq!(server.get_searches(), vec![0]);
+    }
+
+    #[test]
+    fn should_limit_pages_in_flight() {
+        let server = start_server(100);
+        server.set_latency(Duration::from_millis(20));
+        let mut config = server.config();
+        config.search.page_size = 10;
+        config.search.max_in_flight = 2;
+        let (done, result) = search(&config, &["summary"]);
+        assert!(done.is_ok());
+        assert_eq!(result.issues.len(), 100);
+        assert_eq!(server.get_max_active(), 2);
+    }
+
+    #[test]
+    fn should_retry_throttled_page() {
+        let server = start_server(250);
+        server.fail_page(100, 503, 2);
+        let (done, result) = search(&server.config(), &["summary"]);
+        assert!(done.is_ok());
+        assert_eq!(result.issues.len(), 250);
+        let retried = server
+            .get_searches()
+            .iter()
+            .filter(|it| **it == 100)
+            .count();
+        assert_eq!(retried, 3);
+    }
+
+    #[test]
+    fn should_give_up_failed_pages_only() {
+        let server = start_server(250);
+        server.fail_page(200, 502, 10);
+        let mut config = server.config();
+        config.search.max_attempts = 2;
+        let (done, result) = search(&config, &["summary"]);
+        assert_eq!(result.issues.len(), 200);
+        match done {
+            Err(Error::Pages(pages)) => {
+                assert_eq!(pages.len(), 1);
+                assert_eq!((pages[0].start_at, pages[0].attempts), (200, 2));
             }
+            other => panic!("unexpected result {:?}", other),
         }
     }
 
-    fn clean_finished_from_pending(&mut self) {
-        let finished = &mut self.finished;
-        self.pending_jobs.retain(|ref qry| {
-            finished.sort_unstable();
-            finished.binary_search(&qry.startAt).is_err()
-        });
+    #[test]
+    fn should_replay_recorded_search() {
+        let server = start_server(250);
+        server.fail_page(100, 503, 1);
+        let config = server.config();
+        let path = std::env::temp_dir().join(format!("jira-search-{}", std::process::id()));
+
+        let fetcher = Fetcher::new(Auth::bearer("token"), &config).unwrap();
+        let cassette = Cassette::record(&path).unwrap();
+        let (done, recorded) = search_by(fetcher.with_cassette(cassette), &config, &["summary"]);
+        done.unwrap();
+        let served = server.get_searches().len();
+
+        let fetcher = Fetcher::new(Auth::Anonymous, &config).unwrap();
+        let cassette = Cassette::replay(&path).unwrap();
+        let (done, replayed) = search_by(fetcher.with_cassette(cassette), &config, &["summary"]);
+        done.unwrap();
+        assert_eq!(server.get_searches().len(), served);
+        let keys = |result: &QueryResult<Issue<Fields>>| -> Vec<String> {
+            result.issues.iter().map(|it| it.key.clone()).collect()
+        };
+        assert_eq!(keys(&replayed), keys(&recorded));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn should_not_retry_rejected_search() {
+        let server = start_server(10);
+        server.fail_page(0, 400, 1);
+        let (done, _) = search(&server.config(), &["summary"]);
+        match done {
+            Err(Error::Jql(errors)) => assert_eq!(errors.error_messages, vec!["Injected failure"]),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert_eq!(server.get_searches().len(), 1);
     }
 }
@@ -0,0 +1,190 @@
+use rusqlite::{params, Connection};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io;
+
+use super::datatypes::KeyedData;
+use super::records::Records;
+use crate::error::{Error, Result};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS snapshot (
+        id INTEGER PRIMARY KEY,
+        cache TEXT NOT NULL,
+        query TEXT NOT NULL,
+        synced INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS item (
+        snapshot INTEGER NOT NULL REFERENCES snapshot(id),
+        key TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS snapshot_cache ON snapshot(cache, synced);
+    CREATE INDEX IF NOT EXISTS item_key ON item(key, snapshot);
+";
+
+//every fetched set of records kept in a sqlite database, by the cache file
+// they're written to, so that earlier states of items can be looked up
+pub struct Store {
+    path: String,
+    conn: Connection,
+}
+
+//state of an item in a snapshot
+#[derive(Debug, PartialEq)]
+pub struct Revision<T> {
+    pub synced: u64,
+    pub item: T,
+}
+
+impl Store {
+    pub fn open(path: &str) -> Result<Store> {
+        let conn = Connection::open(path).map_err(|e| store_error(path, e))?;
+        conn.execute_batch(SCHEMA)
+            .map_err(|e| store_error(path, e))?;
+        Ok(Store {
+            path: path.to_string(),
+            conn,
+        })
+    }
+
+    //keep given records as a new snapshot of the cache
+    pub(crate) fn save<T>(&mut self, cache: &str, rec: &Records<T>) -> Result<()>
+    where
+        T: Serialize + KeyedData,
+    {
+        let path = &self.path;
+        let tx = self.conn.transaction().map_err(|e| store_error(path, e))?;
+        tx.execute(
+            "INSERT INTO snapshot (cache, query, synced) VALUES (?1, ?2, ?3)",
+            params![cache, rec.query, rec.timestamp as i64],
+        )
+        .map_err(|e| store_error(path, e))?;
+        let snapshot = tx.last_insert_rowid();
+        {
+            let mut insert = tx
+                .prepare("INSERT INTO item (snapshot, key, data) VALUES (?1, ?2, ?3)")
+                .map_err(|e| store_error(path, e))?;
+            for item in &rec.records {
+                let data = serde_json::to_string(item).map_err(|e| store_error(path, e))?;
+                insert
+                    .execute(params![snapshot, item.get_key(), data])
+                    .map_err(|e| store_error(path, e))?;
+            }
+        }
+        tx.commit().map_err(|e| store_error(path, e))?;
+        info!(
+            "Snapshot {} of {} items stored to {}",
+            snapshot,
+            rec.records.len(),
+            path
+        );
+        Ok(())
+    }
+
+    //states of the item of given key in snapshots of the cache, oldest first,
+    // a state is only given when it differs from the previous one
+    pub fn history<T>(&self, cache: &str, key: &str) -> Result<Vec<Revision<T>>>
+    where
+        T: DeserializeOwned,
+    {
+        let path = &self.path;
+        let mut select = self
+            .conn
+            .prepare(
+                "SELECT snapshot.synced, item.data FROM item
+                 JOIN snapshot ON snapshot.id = item.snapshot
+                 WHERE snapshot.cache = ?1 AND item.key = ?2
+                 ORDER BY snapshot.synced, snapshot.id",
+            )
+            .map_err(|e| store_error(path, e))?;
+        let rows = select
+            .query_map(params![cache, key], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(|e| store_error(path, e))?;
+        let mut revisions = Vec::new();
+        let mut last = String::new();
+        for row in rows {
+            let (synced, data) = row.map_err(|e| store_error(path, e))?;
+            if data == last {
+                continue;
+            }
+            revisions.push(Revision {
+                synced: synced as u64,
+                item: serde_json::from_str(&data).map_err(|e| store_error(path, e))?,
+            });
+            last = data;
+        }
+        Ok(revisions)
+    }
+}
+
+fn store_error<E>(path: &str, err: E) -> Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    Error::Cache {
+        path: path.to_string(),
+        source: io::Error::other(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Item {
+        key: String,
+        plan: String,
+    }
+
+    impl KeyedData for Item {
+        fn get_key(&self) -> &str {
+            &self.key
+        }
+    }
+
+    fn items(plans: &[(&str, &str)]) -> Vec<Item> {
+        plans
+            .iter()
+            .map(|(key, plan)| Item {
+                key: key.to_string(),
+                plan: plan.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_keep_every_snapshot() {
+        let path = std::env::temp_dir().join(format!("jira-store-{}.db", std::process::id()));
+        let path = path.display().to_string();
+        let mut store = Store::open(&path).unwrap();
+        let first = items(&[("A-1", "1901"), ("A-2", "1902")]);
+        store
+            .save("ca.json", &Records::synced("project = A", 100, first))
+            .unwrap();
+        let second = items(&[("A-1", "1903"), ("A-2", "1902")]);
+        store
+            .save("ca.json", &Records::synced("project = A", 200, second))
+            .unwrap();
+        store
+            .save(
+                "sys.json",
+                &Records::synced("", 300, items(&[("A-1", "x")])),
+            )
+            .unwrap();
+        drop(store);
+
+        let store = Store::open(&path).unwrap();
+        let history: Vec<Revision<Item>> = store.history("ca.json", "A-1").unwrap();
+        let plans: Vec<(u64, &str)> = history
+            .iter()
+            .map(|it| (it.synced, it.item.plan.as_str()))
+            .collect();
+        assert_eq!(plans, vec![(100, "1901"), (200, "1903")]);
+        assert_eq!(store.history::<Item>("ca.json", "A-2").unwrap().len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,93 @@
+use super::datatypes::KeyedData;
+use std::collections::HashSet;
+
+//issues updated a bit before last sync are fetched again, so that a clock
+// difference with the server loses nothing
+const SYNC_MARGIN_MINUTES: u64 = 5;
+
+//jql of issues updated since last sync, given in minutes before the server
+// time so that time zones of the account and the server don't matter
+pub(crate) fn updated_since(jql: &str, synced: u64, now: u64) -> String {
+    let minutes = now.saturating_sub(synced).div_ceil(60) + SYNC_MARGIN_MINUTES;
+    with_condition(jql, &format!("updated >= -{}m", minutes))
+}
+
+//jql with given condition, put before its ORDER BY clause if any
+fn with_condition(jql: &str, condition: &str) -> String {
+    //ascii lowercase keeps byte positions
+    let (filter, order) = match jql.to_ascii_lowercase().rfind("order by") {
+        Some(pos) => (jql[..pos].trim(), &jql[pos..]),
+        None => (jql.trim(), ""),
+    };
+    let filtered = if filter.is_empty() {
+        condition.to_string()
+    } else {
+        format!("({}) AND {}", filter, condition)
+    };
+    if order.is_empty() {
+        filtered
+    } else {
+        format!("{} {}", filtered, order)
+    }
+}
+
+//cached items replaced by updated ones of the same key, and removed if not
+// found by the search anymore, e.g. deleted or moved to another project
+pub(crate) fn merge<T: KeyedData>(
+    cached: Vec<T>,
+    updated: Vec<T>,
+    keys: &HashSet<String>,
+) -> Vec<T> {
+    let updated_keys: HashSet<String> = updated.iter().map(|it| it.get_key().to_string()).collect();
+    let (kept, removed): (Vec<T>, Vec<T>) = cached
+        .into_iter()
+        .partition(|it| keys.contains(it.get_key()));
+    info!("Sync: {} updated, {} removed", updated.len(), removed.len());
+    let mut merged: Vec<T> = kept
+        .into_iter()
+        .filter(|it| !updated_keys.contains(it.get_key()))
+        .collect();
+    merged.extend(updated);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Item(&'static str, u32);
+
+    impl KeyedData for Item {
+        fn get_key(&self) -> &str {
+            self.0
+        }
+    }
+
+    #[test]
+    fn should_search_updated_since_last_sync() {
+        assert_eq!(
+            updated_since("project = FPB", 1000, 1000 + 3600),
+            "(project = FPB) AND updated >= -65m"
+        );
+        assert_eq!(
+            updated_since("project = FPB ORDER BY key", 1000, 1001),
+            "(project = FPB) AND updated >= -6m ORDER BY key"
+        );
+        assert_eq!(updated_since(" ", 1000, 1000), "updated >= -5m");
+    }
+
+    #[test]
+    fn should_merge_by_key() {
+        let cached = vec![Item("A-1", 1), Item("A-2", 1), Item("A-3", 1)];
+        let updated = vec![Item("A-2", 2), Item("A-4", 2)];
+        let keys: HashSet<String> = ["A-1", "A-2", "A-4"]
+            .iter()
+            .map(|it| it.to_string())
+            .collect();
+        assert_eq!(
+            merge(cached, updated, &keys),
+            vec![Item("A-1", 1), Item("A-2", 2), Item("A-4", 2)]
+        );
+    }
+}
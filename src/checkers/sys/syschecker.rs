@@ -1,40 +1,61 @@
 use super::sysitem::SysItem;
-use crate::checkers::utils::get_leftmost;
+use crate::checkers::table::{cell, Table};
+use crate::checkers::utils::{write_report, NO_ITEMS};
+use crate::error::Result;
 
 use std::fmt::format;
 use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
 const BANNER: &str =
     "----------------------------------------------------------------------------\n";
 
-pub fn analyze_results(items: &[SysItem]) {
+pub fn analyze_results(items: &[SysItem], out_dir: &Path) -> Result<()> {
     //dumping
-    let mut buf_writer = BufWriter::new(File::create("sys-feature-details.txt").unwrap());
-    dump_all(&mut buf_writer, &items);
+    let table = details(items);
+    write_report(out_dir, "sys-feature-details.txt", |buf_writer| {
+        dump_all(buf_writer, &table)
+    })?;
+    table.export(out_dir)?;
 
     info!("Analyzed done for this query!\n");
+    Ok(())
 }
 
-fn dump_all(buf_writer: &mut BufWriter<File>, items: &[SysItem]) {
-    let total = items.len();
+pub(crate) fn details(items: &[SysItem]) -> Table {
+    let mut table = Table::new("sys-feature-details")
+        .hidden("feature")
+        .cut("summary", 20)
+        .cut("area", 12)
+        .cut("title", 40)
+        .cut("key", 10)
+        .cut("status", 8)
+        .cut("release", 10);
+    for it in items {
+        table.push(vec![
+            cell(&it.get_fid()),
+            cell(&it.summary),
+            cell(&it.area),
+            cell(&it.title),
+            cell(&it.key),
+            cell(&it.status),
+            cell(&it.release),
+        ]);
+    }
+    table
+}
+
+fn dump_all(buf_writer: &mut BufWriter<File>, table: &Table) -> io::Result<()> {
+    let total = table.len();
     let summary = format(format_args!(
         "@@ Feature analysis: {} issues in total\n",
         total
     ));
     info!("Got {} system level features", total);
-    buf_writer.write_all(summary.as_bytes()).unwrap();
-    buf_writer.write_all(BANNER.as_bytes()).unwrap();
-
-    items.iter().for_each(|it| {
-        let line = format(format_args!(
-            "{:20}|{:12}|{:40}|{:10}|{:8}|{:10}\n",
-            get_leftmost(&it.summary, 20),
-            get_leftmost(&it.area, 12),
-            get_leftmost(&it.title, 40),
-            get_leftmost(&it.key, 10),
-            get_leftmost(&it.status, 8),
-            get_leftmost(&it.release, 10)
-        ));
-        buf_writer.write_all(line.as_bytes()).unwrap();
-    });
+    buf_writer.write_all(summary.as_bytes())?;
+    if table.is_empty() {
+        return buf_writer.write_all(NO_ITEMS.as_bytes());
+    }
+    buf_writer.write_all(BANNER.as_bytes())?;
+    table.write_text(buf_writer)
 }
@@ -3,23 +3,18 @@ use crate::checkers::utils::*;
 use crate::query::issue::Issue;
 use serde_json::Value;
 
-const SYS_FIELDS_SUMMARY: &str = "summary";
-const SYS_FIELDS_AREA: &str = "customfield_38711";
-const SYS_FIELDS_STATUS: &str = "status";
-const SYS_FIELDS_TITLE: &str = "customfield_38703";
-const SYS_FIELDS_RELEASE: &str = "customfield_38724";
+//keys of fields with their default names, overridden by [fields] of config
+const SYS_FIELDS_SUMMARY: (&str, &str) = ("summary", "summary");
+const SYS_FIELDS_AREA: (&str, &str) = ("area", "Area");
+const SYS_FIELDS_STATUS: (&str, &str) = ("status", "status");
+const SYS_FIELDS_TITLE: (&str, &str) = ("title", "Title");
+const SYS_FIELDS_RELEASE: (&str, &str) = ("release", "Release");
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct SysFields {
-    #[serde(rename = "customfield_38703")]
     pub title: Value,
-
-    #[serde(rename = "customfield_38724")]
     pub release: Value,
-
     pub summary: String,
-
-    #[serde(rename = "customfield_38711")]
     pub area: Value,
     pub status: Value,
 }
@@ -49,7 +44,7 @@ impl SysIssue {
 
 impl ParsedData for SysIssue {
     //get field lists
-    fn get_field_list() -> Vec<String> {
+    fn get_field_list() -> Vec<(&'static str, &'static str)> {
         vec![
             SYS_FIELDS_SUMMARY,
             SYS_FIELDS_AREA,
@@ -57,9 +52,6 @@ impl ParsedData for SysIssue {
             SYS_FIELDS_TITLE,
             SYS_FIELDS_RELEASE,
         ]
-        .iter()
-        .map(|x| x.to_string())
-        .collect()
     }
 }
 
@@ -82,12 +74,12 @@ pub(crate) mod tests {
             "self":"https://jiradc.int.net.nokia.com/rest/api/2/issue/4280470",
             "key":"FFB-8738",
             "fields":{"summary":"Sample 5G Feature | 5GC008888",
-            "customfield_38711":null,
-            "customfield_38724":[{
+            "area":null,
+            "release":[{
                 "self":"https://jiradc.int.net.nokia.com/rest/api/2/customFieldOption/209818",
                 "value":"5G Future Release",
                 "id":"209818"}],
-            "customfield_38703":null,
+            "title":null,
             "status":{"self":"https://jiradc.int.net.nokia.com/rest/api/2/status/10044",
                 "description":"When new FI, User Story or Task is created.",
                 "iconUrl":"https://jiradc.int.net.nokia.com/images/icons/statuses/open.png",
@@ -1,5 +1,6 @@
-use super::super::datatypes::StoredData;
+use super::super::datatypes::{KeyedData, LintedData, StoredData};
 use super::sysissue::SysIssue;
+use crate::checkers::utils::NA_STRING;
 use std::cmp::Ord;
 use std::cmp::Ordering;
 
@@ -67,6 +68,32 @@ impl StoredData for SysItem {
     }
 }
 
+impl KeyedData for SysItem {
+    fn get_key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl LintedData for SysItem {
+    fn get_id(&self) -> &str {
+        &self.key
+    }
+
+    fn lint(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.title == NA_STRING {
+            problems.push("no feature title".to_string());
+        }
+        if self.release.is_empty() {
+            problems.push("no release".to_string());
+        }
+        if self.area.is_empty() {
+            problems.push("no area".to_string());
+        }
+        problems
+    }
+}
+
 #[cfg(test)]
 mod tests {
     extern crate serde;
@@ -97,4 +124,10 @@ mod tests {
         item1.area = "Operability xxx".to_string();
         assert!(item1.is_oam_feature(), "{:?}", item);
     }
+
+    #[test]
+    fn should_lint_missing_title_and_area() {
+        let item = get_test_item();
+        assert_eq!(item.lint(), vec!["no feature title", "no area"]);
+    }
 }
@@ -0,0 +1,260 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::io::{self, Write};
+use std::path::Path;
+
+use super::utils::{format_table, get_leftmost, write_report, NA_STRING};
+use crate::error::Result;
+
+//how a column is shown in text reports, exports always give full values
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Layout {
+    //padded to the width and cut if longer
+    Cut,
+    //padded to the width, never cut
+    Pad,
+    //right aligned to the width, never cut
+    PadRight,
+    //left out, e.g. a column only needed by exports
+    Hidden,
+}
+
+#[derive(Debug, Clone)]
+struct Column {
+    name: String,
+    width: usize,
+    layout: Layout,
+}
+
+//rows of a report with named columns, shown as fixed-width text and exported
+// as csv and json, named after the files they're exported to
+#[derive(Debug, Clone)]
+pub struct Table {
+    name: String,
+    columns: Vec<Column>,
+    rows: Vec<Vec<Value>>,
+}
+
+//value of a cell, null for missing ones
+pub fn cell<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+//value as shown in text reports
+pub(crate) fn to_text(value: &Value) -> String {
+    match value {
+        Value::Null => NA_STRING.to_string(),
+        Value::String(text) => text.clone(),
+        Value::Array(values) => values.iter().map(to_text).collect::<Vec<_>>().join(","),
+        other => other.to_string(),
+    }
+}
+
+impl Table {
+    pub fn new(name: &str) -> Table {
+        Table {
+            name: name.to_string(),
+            columns: Vec::new(),
+            rows: Vec::new(),
+        }
+    }
+
+    fn with(mut self, name: &str, width: usize, layout: Layout) -> Table {
+        self.columns.push(Column {
+            name: name.to_string(),
+            width,
+            layout,
+        });
+        self
+    }
+
+    pub fn cut(self, name: &str, width: usize) -> Table {
+        self.with(name, width, Layout::Cut)
+    }
+
+    pub fn pad(self, name: &str, width: usize) -> Table {
+        self.with(name, width, Layout::Pad)
+    }
+
+    pub fn pad_right(self, name: &str, width: usize) -> Table {
+        self.with(name, width, Layout::PadRight)
+    }
+
+    pub fn hidden(self, name: &str) -> Table {
+        self.with(name, 0, Layout::Hidden)
+    }
+
+    //cells in order of the columns
+    pub fn push(&mut self, row: Vec<Value>) {
+        debug_assert_eq!(row.len(), self.columns.len());
+        self.rows.push(row);
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_columns(&self) -> Vec<&str> {
+        self.columns.iter().map(|it| it.name.as_str()).collect()
+    }
+
+    pub fn get_rows(&self) -> &[Vec<Value>] {
+        &self.rows
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    //one line per row in given column widths, without header
+    pub fn write_text<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for row in &self.rows {
+            let cells: Vec<String> = self
+                .columns
+                .iter()
+                .zip(row)
+                .filter_map(|(column, value)| {
+                    let text = to_text(value);
+                    let width = column.width;
+                    match column.layout {
+                        Layout::Cut => Some(format!("{:width$}", get_leftmost(&text, width))),
+                        Layout::Pad => Some(format!("{:width$}", text)),
+                        Layout::PadRight => Some(format!("{:>width$}", text)),
+                        Layout::Hidden => None,
+                    }
+                })
+                .collect();
+            writeln!(writer, "{}", cells.join("|"))?;
+        }
+        Ok(())
+    }
+
+    //header and rows in columns sized by content
+    pub fn format_sized(&self) -> String {
+        let shown = |column: &&Column| column.layout != Layout::Hidden;
+        let header: Vec<String> = self
+            .columns
+            .iter()
+            .filter(shown)
+            .map(|it| it.name.clone())
+            .collect();
+        let rows: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| {
+                self.columns
+                    .iter()
+                    .zip(row)
+                    .filter(|(column, _)| shown(column))
+                    .map(|(_, value)| to_text(value))
+                    .collect()
+            })
+            .collect();
+        format_table(&header, &rows)
+    }
+
+    //header line and a line per row, missing values are empty
+    pub fn to_csv(&self) -> String {
+        let quote = |text: String| {
+            if text.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", text.replace('"', "\"\""))
+            } else {
+                text
+            }
+        };
+        let header: Vec<String> = self
+            .columns
+            .iter()
+            .map(|it| quote(it.name.clone()))
+            .collect();
+        let mut output = header.join(",") + "\r\n";
+        for row in &self.rows {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|value| match value {
+                    Value::Null => String::new(),
+                    other => quote(to_text(other)),
+                })
+                .collect();
+            output += &(cells.join(",") + "\r\n");
+        }
+        output
+    }
+
+    //an object per row keyed by column names
+    pub fn to_json(&self) -> Value {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let object: Map<String, Value> = self
+                    .columns
+                    .iter()
+                    .map(|it| it.name.clone())
+                    .zip(row.iter().cloned())
+                    .collect();
+                Value::Object(object)
+            })
+            .collect();
+        Value::Array(rows)
+    }
+
+    //<name>.csv and <name>.json under given directory
+    pub fn export(&self, out_dir: &Path) -> Result<()> {
+        write_report(out_dir, &format!("{}.csv", self.name), |writer| {
+            writer.write_all(self.to_csv().as_bytes())
+        })?;
+        write_report(out_dir, &format!("{}.json", self.name), |writer| {
+            serde_json::to_writer_pretty(&mut *writer, &self.to_json())
+                .map_err(io::Error::other)?;
+            writeln!(writer)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Table {
+        let mut table = Table::new("sample").cut("title", 5).pad_right("efforts", 4);
+        table.push(vec![cell(&"Some, \"long\" title"), cell(&Some(10))]);
+        table.push(vec![cell(&"Short"), cell(&None::<u32>)]);
+        table
+    }
+
+    #[test]
+    fn should_cut_only_text() {
+        let mut text = Vec::new();
+        table().write_text(&mut text).unwrap();
+        assert_eq!(String::from_utf8(text).unwrap(), "Some,|  10\nShort|  NA\n");
+    }
+
+    #[test]
+    fn should_export_hidden_columns_only() {
+        let mut table = Table::new("hidden").hidden("feature").cut("title", 5);
+        table.push(vec![cell(&"F1"), cell(&"Title")]);
+        let mut text = Vec::new();
+        table.write_text(&mut text).unwrap();
+        assert_eq!(String::from_utf8(text).unwrap(), "Title\n");
+        assert!(!table.format_sized().contains("feature"));
+        assert_eq!(table.to_csv(), "feature,title\r\nF1,Title\r\n");
+    }
+
+    #[test]
+    fn should_export_full_values() {
+        let table = table();
+        assert_eq!(
+            table.to_csv(),
+            "title,efforts\r\n\"Some, \"\"long\"\" title\",10\r\nShort,\r\n"
+        );
+        let json = table.to_json();
+        assert_eq!(json[0]["title"], "Some, \"long\" title");
+        assert_eq!(json[0]["efforts"], 10);
+        assert_eq!(json[1]["efforts"], Value::Null);
+    }
+}
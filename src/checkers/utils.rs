@@ -3,21 +3,60 @@ extern crate serde_json;
 
 use self::itertools::Itertools;
 use self::serde_json::Value;
+use crate::error::{Error, Result};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
 pub const NA_STRING: &str = "NA";
+const MAX_COLUMN_WIDTH: usize = 40;
 
-//Get a slice of the leftmost given characters
-pub fn get_leftmost(raw: &str, total: usize) -> &str {
-    let max = raw.find('\n').map_or(raw.len(), |x| x);
-    if max > total {
-        let mut end = total;
-        //find next char boundary
-        while !raw.is_char_boundary(end) {
-            end += 1;
+//given in reports of a search which found nothing, failed ones give no report
+pub(crate) const NO_ITEMS: &str = "@@ No items found by the search\n";
+
+pub use crate::text::get_leftmost;
+
+//Create report file under given directory and fill it by the writer function
+pub(crate) fn write_report<F>(out_dir: &Path, fname: &str, write: F) -> Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+{
+    let path = out_dir.join(fname);
+    let report_error = |source| Error::Report {
+        path: path.display().to_string(),
+        source,
+    };
+    let mut buf_writer = BufWriter::new(File::create(&path).map_err(report_error)?);
+    write(&mut buf_writer)
+        .and_then(|_| buf_writer.flush())
+        .map_err(report_error)
+}
+
+//left aligned columns sized by content, long values are cut
+pub(crate) fn format_table(header: &[String], rows: &[Vec<String>]) -> String {
+    let cut = |value: &str| get_leftmost(value, MAX_COLUMN_WIDTH).to_string();
+    let mut widths: Vec<usize> = header.iter().map(|it| cut(it).chars().count()).collect();
+    for row in rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cut(value).chars().count());
         }
-        &raw[0..end]
-    } else {
-        &raw[0..max]
     }
+
+    let format_row = |row: &[String]| {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(value, width)| format!("{:width$}", cut(value), width = width))
+            .collect();
+        cells.join("|").trim_end().to_string() + "\n"
+    };
+
+    let mut output = format_row(header);
+    let separator: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+    output += &(separator.join("+") + "\n");
+    for row in rows {
+        output += &format_row(row);
+    }
+    output
 }
 
 /// field extraction utilities
@@ -56,6 +95,21 @@ pub(crate) fn get_wrapped_string<'a>(value: &'a Value, na: &'static str) -> &'a
     }
 }
 
+//Get a readable string for any field value
+pub(crate) fn get_display_string(value: &Value) -> String {
+    match value {
+        Value::Null => "".to_string(),
+        Value::String(ref some) => some.clone(),
+        Value::Array(ref values) => values.iter().map(get_display_string).join(","),
+        Value::Object(ref obj) => ["value", "name", "displayName", "key"]
+            .iter()
+            .filter_map(|attr| obj.get(*attr))
+            .next()
+            .map_or_else(|| value.to_string(), get_display_string),
+        _ => value.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -66,4 +120,29 @@ mod tests {
         assert_eq!(get_leftmost("Löwe 老虎", 2), "Lö");
         assert_eq!(get_leftmost("Löwe 老虎", 5), "Löwe");
     }
+
+    #[test]
+    fn should_display_wrapped_values() {
+        let value: Value = serde_json::from_str(
+            r#"[{"value": "rel1"}, {"name": "Done", "id": "1"}, 8.0, null, "text"]"#,
+        )
+        .unwrap();
+        assert_eq!(get_display_string(&value), "rel1,Done,8.0,,text");
+    }
+
+    #[test]
+    fn should_format_table_by_widest_cells() {
+        let header = vec!["key".to_string(), "status".to_string()];
+        let rows = vec![
+            vec!["FPB-1".to_string(), "Done".to_string()],
+            vec!["FPB-100".to_string(), "In Progress".to_string()],
+        ];
+        assert_eq!(
+            format_table(&header, &rows),
+            "key    |status\n\
+             -------+-----------\n\
+             FPB-1  |Done\n\
+             FPB-100|In Progress\n"
+        );
+    }
 }
@@ -0,0 +1,771 @@
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use typed_headers::Credentials;
+
+use crate::checkers::fb::FbCalendar;
+
+pub const CONFIG_FILE: &str = "jira-reporter.toml";
+
+const DEFAULT_BASE_URL: &str = "https://jiradc.int.net.nokia.com";
+const DEFAULT_API_PATH: &str = "/rest/api/2";
+const DEFAULT_PROXY_URL: &str = "http://10.144.1.10:8080";
+const DEFAULT_CREDENTIAL_FILE: &str = ".jira-reporter.netrc";
+
+//environment variables taking precedence over the config file
+const ENV_BASE_URL: &str = "JIRA_BASE_URL";
+const ENV_API_PATH: &str = "JIRA_API_PATH";
+const ENV_CA_BUNDLE: &str = "JIRA_CA_BUNDLE";
+const ENV_AUTH: &str = "JIRA_AUTH";
+const ENV_CREDENTIAL_FILE: &str = "JIRA_CREDENTIAL_FILE";
+const ENV_PROXY_URL: &str = "JIRA_PROXY";
+const ENV_PROXY_USER: &str = "JIRA_PROXY_USER";
+const ENV_PROXY_PASSWORD: &str = "JIRA_PROXY_PASSWORD";
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "{}", err),
+            ConfigError::Toml(err) => write!(f, "{}", err),
+            ConfigError::Invalid(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Config {
+    pub jira: JiraConfig,
+    pub proxy: ProxyConfig,
+    pub search: SearchConfig,
+    pub history: HistoryConfig,
+    pub calendar: FbCalendar,
+
+    //issue fields of checkers by their keys, given by name or id, e.g.
+    // team = "Squad", in case the server names them otherwise
+    pub fields: HashMap<String, String>,
+
+    //report pipelines, given as [[report]] tables
+    #[serde(rename = "report")]
+    pub reports: Vec<ReportConfig>,
+
+    //issue fields of dynamic reports, given as [[schema]] tables
+    #[serde(rename = "schema")]
+    pub schemas: Vec<SchemaConfig>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct JiraConfig {
+    //server address without trailing api path, e.g. https://jira.example.com
+    pub base_url: String,
+
+    //REST api path appended to base_url
+    pub api_path: String,
+
+    //extra PEM encoded CA certificate to trust for https servers
+    pub ca_bundle: Option<String>,
+
+    //how to authenticate against the server
+    pub auth: AuthMethod,
+
+    //netrc style file of login details, in home directory by default
+    pub credential_file: Option<String>,
+}
+
+impl JiraConfig {
+    //host name used as machine in credential file
+    pub fn get_host(&self) -> String {
+        let url = self.base_url.split("://").last().unwrap_or("");
+        let host = url.split(['/', ':']).next().unwrap_or("");
+        host.to_string()
+    }
+
+    //issue pages are given by appending the key
+    pub fn get_browse_url(&self) -> String {
+        format!("{}/browse/", self.base_url.trim_end_matches('/'))
+    }
+
+    pub fn get_credential_file(&self) -> PathBuf {
+        match (&self.credential_file, env::var_os("HOME")) {
+            (Some(path), _) => PathBuf::from(path),
+            (None, Some(home)) => Path::new(&home).join(DEFAULT_CREDENTIAL_FILE),
+            (None, None) => PathBuf::from(DEFAULT_CREDENTIAL_FILE),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthMethod {
+    //username and password in every request
+    Basic,
+
+    //personal access token given as the password
+    Bearer,
+
+    //cookie of a session created once by username and password
+    Session,
+}
+
+impl AuthMethod {
+    fn parse(name: &str) -> Option<AuthMethod> {
+        match name.trim().to_lowercase().as_str() {
+            "basic" => Some(AuthMethod::Basic),
+            "bearer" => Some(AuthMethod::Bearer),
+            "session" => Some(AuthMethod::Session),
+            _ => None,
+        }
+    }
+}
+
+impl Default for JiraConfig {
+    fn default() -> Self {
+        JiraConfig {
+            base_url: DEFAULT_BASE_URL.to_string(),
+            api_path: DEFAULT_API_PATH.to_string(),
+            ca_bundle: None,
+            auth: AuthMethod::Basic,
+            credential_file: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ProxyConfig {
+    //empty url means a direct connection
+    pub url: String,
+
+    //proxy credentials, unrelated to the jira login
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        ProxyConfig {
+            url: DEFAULT_PROXY_URL.to_string(),
+            username: None,
+            password: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SearchConfig {
+    //attempts per page before giving up, including the first one
+    pub max_attempts: u32,
+
+    //exponential backoff between attempts, randomized up to the current ceiling
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+
+    //issues requested per page
+    pub page_size: usize,
+
+    //pages requested at the same time
+    pub max_in_flight: usize,
+
+    //requests per second sent to the server, 0 for no limit
+    pub requests_per_second: f64,
+}
+
+//slowest limit of requests, waiting for longer than this is surely a mistake
+const MIN_REQUESTS_PER_SECOND: f64 = 0.001;
+
+impl SearchConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let rate = self.requests_per_second;
+        if rate != 0.0 && !(rate.is_finite() && rate >= MIN_REQUESTS_PER_SECOND) {
+            return Err(format!(
+                "{} requests per second, give 0 for no limit or at least {}",
+                rate, MIN_REQUESTS_PER_SECOND
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        SearchConfig {
+            max_attempts: 5,
+            base_delay_ms: 500,
+            max_delay_ms: 30_000,
+            page_size: 100,
+            max_in_flight: 4,
+            requests_per_second: 5.0,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct HistoryConfig {
+    //sqlite file keeping every fetched snapshot of caches, none kept if absent
+    pub database: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckerKind {
+    Sys,
+    Fs2,
+    Ca,
+
+    //issues of a schema declared in config, listed as a table
+    Dynamic,
+}
+
+impl CheckerKind {
+    //results of other checkers needed by this one
+    pub fn required_inputs(self) -> &'static [CheckerKind] {
+        match self {
+            CheckerKind::Sys => &[],
+            CheckerKind::Fs2 => &[CheckerKind::Sys],
+            CheckerKind::Ca => &[CheckerKind::Sys, CheckerKind::Fs2],
+            CheckerKind::Dynamic => &[],
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ReportConfig {
+    //unique name referred by other reports' inputs
+    pub name: String,
+    pub checker: CheckerKind,
+    pub jql: String,
+
+    //local cache file for fetched items
+    pub cache: String,
+
+    //names of reports whose items are consumed by this checker
+    #[serde(default)]
+    pub inputs: Vec<String>,
+
+    //directory for generated report files
+    #[serde(default = "default_output_dir")]
+    pub output_dir: String,
+
+    //name of the schema of issues, only for dynamic checker
+    #[serde(default)]
+    pub schema: Option<String>,
+}
+
+//issue fields of a dynamic report and how to extract their values
+#[derive(Deserialize, Debug, Clone)]
+pub struct SchemaConfig {
+    pub name: String,
+    pub fields: Vec<FieldSpec>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct FieldSpec {
+    //name of the value in records, e.g. "feature_id"
+    pub name: String,
+
+    //jira field by name or id, e.g. "Feature ID"
+    pub field: String,
+
+    #[serde(default)]
+    pub kind: FieldKind,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldKind {
+    //plain text
+    #[default]
+    String,
+
+    //value of a select list, e.g. {"value": "EFS"}
+    Option,
+
+    //values of a multi select list, e.g. releases
+    Values,
+
+    //display name of a user
+    User,
+
+    Number,
+
+    //time tracking given in seconds, e.g. original estimate
+    Hours,
+
+    //feature build code, e.g. "1809"
+    Fb,
+}
+
+fn default_output_dir() -> String {
+    ".".to_string()
+}
+
+impl Config {
+    //load from given file if present, then apply environment overrides
+    pub fn load(path: &str) -> Result<Config, ConfigError> {
+        let config = match fs::read_to_string(path) {
+            Ok(content) => Config::parse(&content)?,
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => {
+                warn!("No config file found at {}, using defaults!", path);
+                Config::default()
+            }
+            Err(err) => return Err(ConfigError::Io(err)),
+        };
+        Ok(config.with_overrides(|key| env::var(key).ok()))
+    }
+
+    pub fn parse(content: &str) -> Result<Config, ConfigError> {
+        let config: Config = toml::from_str(content).map_err(ConfigError::Toml)?;
+        //used by every command, not only by reports
+        if let Err(reason) = config.search.validate() {
+            return Err(ConfigError::Invalid(format!("search: {}", reason)));
+        }
+        Ok(config)
+    }
+
+    //override settings by given lookup, normally the process environment
+    pub fn with_overrides<F>(mut self, lookup: F) -> Config
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if let Some(url) = lookup(ENV_BASE_URL) {
+            self.jira.base_url = url;
+        }
+        if let Some(path) = lookup(ENV_API_PATH) {
+            self.jira.api_path = path;
+        }
+        if let Some(path) = lookup(ENV_CA_BUNDLE) {
+            self.jira.ca_bundle = Some(path);
+        }
+        if let Some(path) = lookup(ENV_CREDENTIAL_FILE) {
+            self.jira.credential_file = Some(path);
+        }
+        if let Some(name) = lookup(ENV_AUTH) {
+            match AuthMethod::parse(&name) {
+                Some(method) => self.jira.auth = method,
+                None => warn!("Unknown auth method {} in {}, ignored!", name, ENV_AUTH),
+            }
+        }
+        if let Some(url) = lookup(ENV_PROXY_URL) {
+            self.proxy.url = url;
+        }
+        if let Some(user) = lookup(ENV_PROXY_USER) {
+            self.proxy.username = Some(user);
+        }
+        if let Some(password) = lookup(ENV_PROXY_PASSWORD) {
+            self.proxy.password = Some(password);
+        }
+        self
+    }
+
+    //caches moved into given directory and no history kept, so that a run
+    // which isn't real, e.g. from a cassette, leaves real ones alone
+    pub fn isolated(mut self, dir: &str) -> Config {
+        for report in &mut self.reports {
+            let fname = Path::new(&report.cache)
+                .file_name()
+                .map_or_else(|| report.name.clone().into(), |it| it.to_os_string());
+            report.cache = Path::new(dir).join(fname).display().to_string();
+        }
+        self.history.database = None;
+        self
+    }
+
+    //full uri for given REST resource, e.g. "search"
+    pub fn api_uri(&self, resource: &str) -> String {
+        format!(
+            "{}/{}/{}",
+            self.jira.base_url.trim_end_matches('/'),
+            self.jira.api_path.trim_matches('/'),
+            resource.trim_start_matches('/')
+        )
+    }
+
+    pub fn search_uri(&self) -> String {
+        self.api_uri("search")
+    }
+
+    //field configured for given key, default name of the checker otherwise
+    pub fn get_field<'a>(&'a self, key: &str, default: &'a str) -> &'a str {
+        self.fields.get(key).map_or(default, |it| it.as_str())
+    }
+
+    //catalogue of all fields, to find custom ones by name
+    pub fn field_uri(&self) -> String {
+        self.api_uri("field")
+    }
+
+    //session resource lives outside of the REST api path
+    pub fn session_uri(&self) -> String {
+        format!(
+            "{}/rest/auth/1/session",
+            self.jira.base_url.trim_end_matches('/')
+        )
+    }
+
+    pub fn find_report(&self, name: &str) -> Option<&ReportConfig> {
+        self.reports.iter().find(|it| it.name == name)
+    }
+
+    pub fn find_schema(&self, name: &str) -> Option<&SchemaConfig> {
+        self.schemas.iter().find(|it| it.name == name)
+    }
+
+    //schema of given dynamic report, validated by ordered_reports
+    pub fn get_schema(&self, report: &ReportConfig) -> Result<&SchemaConfig, ConfigError> {
+        report
+            .schema
+            .as_ref()
+            .and_then(|name| self.find_schema(name))
+            .ok_or_else(|| ConfigError::Invalid(format!("{}: unknown schema", report.name)))
+    }
+
+    //reports ordered so that every report comes after all of its inputs
+    pub fn ordered_reports(&self) -> Result<Vec<&ReportConfig>, ConfigError> {
+        self.validate_reports()?;
+
+        let mut ordered: Vec<&ReportConfig> = Vec::with_capacity(self.reports.len());
+        let mut done: HashSet<&str> = HashSet::new();
+        while ordered.len() < self.reports.len() {
+            let ready: Vec<&ReportConfig> = self
+                .reports
+                .iter()
+                .filter(|it| !done.contains(it.name.as_str()))
+                .filter(|it| it.inputs.iter().all(|x| done.contains(x.as_str())))
+                .collect();
+            if ready.is_empty() {
+                return Err(ConfigError::Invalid(
+                    "circular dependency between reports".to_string(),
+                ));
+            }
+            for report in ready {
+                done.insert(&report.name);
+                ordered.push(report);
+            }
+        }
+        Ok(ordered)
+    }
+
+    fn validate_reports(&self) -> Result<(), ConfigError> {
+        let invalid = |msg: String| Err(ConfigError::Invalid(msg));
+        if let Err(reason) = self.calendar.validate() {
+            return invalid(format!("calendar: {}", reason));
+        }
+        let mut names = HashSet::new();
+        let mut caches = HashSet::new();
+        let mut outputs = HashSet::new();
+        for report in &self.reports {
+            if !names.insert(report.name.as_str()) {
+                return invalid(format!("duplicated report name {}", report.name));
+            }
+            if !caches.insert(report.cache.as_str()) {
+                return invalid(format!(
+                    "{}: duplicated cache {}",
+                    report.name, report.cache
+                ));
+            }
+            //reports of dynamic checker are named after the report, others not
+            let output = (report.checker, report.output_dir.as_str());
+            if report.checker != CheckerKind::Dynamic && !outputs.insert(output) {
+                return invalid(format!(
+                    "{}: duplicated output directory {} of {:?} checker",
+                    report.name, report.output_dir, report.checker
+                ));
+            }
+        }
+
+        for schema in &self.schemas {
+            let mut fields = HashSet::new();
+            for field in &schema.fields {
+                if !fields.insert(field.name.as_str()) {
+                    return invalid(format!("{}: duplicated field {}", schema.name, field.name));
+                }
+            }
+        }
+
+        for report in &self.reports {
+            if report.checker == CheckerKind::Dynamic {
+                self.get_schema(report)?;
+            }
+            //checkers take a single input of each kind
+            let mut kinds = Vec::new();
+            for input in &report.inputs {
+                match self.find_report(input) {
+                    Some(upstream) if kinds.contains(&upstream.checker) => {
+                        return invalid(format!(
+                            "{}: more than one input of {:?} checker, e.g. {}",
+                            report.name, upstream.checker, input
+                        ));
+                    }
+                    Some(upstream) => kinds.push(upstream.checker),
+                    None => {
+                        return invalid(format!("{}: unknown input {}", report.name, input));
+                    }
+                }
+            }
+            for required in report.checker.required_inputs() {
+                if !kinds.contains(required) {
+                    return invalid(format!(
+                        "{}: missing input of {:?} checker",
+                        report.name, required
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ProxyConfig {
+    pub fn get_url(&self) -> Option<&str> {
+        if self.url.is_empty() {
+            None
+        } else {
+            Some(&self.url)
+        }
+    }
+
+    pub fn get_credentials(&self) -> Option<Credentials> {
+        match (&self.username, &self.password) {
+            (Some(user), Some(password)) => Credentials::basic(user, password).ok(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_use_defaults_for_missing_sections() {
+        let config = Config::parse("[jira]\nbase_url = \"https://jira.example.com/\"\n").unwrap();
+        assert_eq!(config.jira.api_path, DEFAULT_API_PATH);
+        assert_eq!(config.jira.ca_bundle, None);
+        assert_eq!(config.search.max_attempts, 5);
+        assert_eq!(config.search.page_size, 100);
+        assert_eq!(config.proxy.get_url(), Some(DEFAULT_PROXY_URL));
+        assert_eq!(
+            config.search_uri(),
+            "https://jira.example.com/rest/api/2/search"
+        );
+    }
+
+    #[test]
+    fn should_override_default_fields() {
+        let config = Config::parse("[fields]\nteam = \"customfield_38727\"\n").unwrap();
+        assert_eq!(config.get_field("team", "Team"), "customfield_38727");
+        assert_eq!(config.get_field("target", "Target"), "Target");
+    }
+
+    #[test]
+    fn should_reject_unusable_request_rate() {
+        let rate =
+            |value: &str| Config::parse(&format!("[search]\nrequests_per_second = {}", value));
+        assert!(rate("0.0").is_ok());
+        assert!(rate("0.5").is_ok());
+        for value in &["nan", "inf", "-1.0", "1e-20"] {
+            match rate(value) {
+                Err(ConfigError::Invalid(reason)) => {
+                    assert!(reason.contains("requests per second"))
+                }
+                other => panic!("{} accepted: {:?}", value, other),
+            }
+        }
+    }
+
+    #[test]
+    fn should_treat_empty_proxy_as_direct() {
+        let config = Config::parse("[proxy]\nurl = \"\"\n").unwrap();
+        assert_eq!(config.proxy.get_url(), None);
+        assert!(config.proxy.get_credentials().is_none());
+    }
+
+    #[test]
+    fn should_override_by_environment() {
+        let config = Config::default().with_overrides(|key| match key {
+            ENV_BASE_URL => Some("http://localhost:8080".to_string()),
+            ENV_PROXY_URL => Some("".to_string()),
+            ENV_PROXY_USER => Some("proxy".to_string()),
+            ENV_AUTH => Some("Bearer".to_string()),
+            _ => None,
+        });
+        assert_eq!(
+            config.search_uri(),
+            "http://localhost:8080/rest/api/2/search"
+        );
+        assert_eq!(config.proxy.get_url(), None);
+        assert_eq!(config.proxy.username, Some("proxy".to_string()));
+        assert_eq!(config.jira.auth, AuthMethod::Bearer);
+        assert_eq!(config.jira.get_host(), "localhost");
+        assert_eq!(
+            config.session_uri(),
+            "http://localhost:8080/rest/auth/1/session"
+        );
+    }
+
+    const REPORTS: &str = r#"
+        [[report]]
+        name = "ca"
+        checker = "ca"
+        jql = "issuetype = \"Competence Area\""
+        cache = "ca-items.json"
+        inputs = ["sys", "fs2"]
+        output_dir = "reports"
+
+        [[report]]
+        name = "fs2"
+        checker = "fs2"
+        jql = "issuetype = \"Effort Estimation\""
+        cache = "fs2-items.json"
+        inputs = ["sys"]
+
+        [[report]]
+        name = "sys"
+        checker = "sys"
+        jql = "issuetype = \"Customer Feature\""
+        cache = "sys-items.json"
+    "#;
+
+    #[test]
+    fn should_order_reports_by_inputs() {
+        let config = Config::parse(REPORTS).unwrap();
+        assert_eq!(config.reports[0].output_dir, "reports");
+        assert_eq!(config.reports[1].output_dir, ".");
+
+        let names: Vec<&str> = config
+            .ordered_reports()
+            .unwrap()
+            .iter()
+            .map(|it| it.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["sys", "fs2", "ca"]);
+    }
+
+    #[test]
+    fn should_reject_missing_checker_input() {
+        let config = Config::parse(&REPORTS.replace(r#"["sys", "fs2"]"#, r#"["sys"]"#));
+        match config.unwrap().ordered_reports() {
+            Err(ConfigError::Invalid(msg)) => assert!(msg.contains("Fs2"), "{}", msg),
+            _ => panic!("should reject ca report without fs2 input!"),
+        }
+    }
+
+    #[test]
+    fn should_reject_inputs_of_same_checker() {
+        let second = r#"
+        [[report]]
+        name = "sys2"
+        checker = "sys"
+        jql = "issuetype = \"Customer Feature\""
+        cache = "sys2-items.json"
+        output_dir = "sys2"
+        "#;
+        let config = format!("{}{}", REPORTS, second).replace(
+            r#"inputs = ["sys", "fs2"]"#,
+            r#"inputs = ["sys", "fs2", "sys2"]"#,
+        );
+        match Config::parse(&config).unwrap().ordered_reports() {
+            Err(ConfigError::Invalid(msg)) => assert!(msg.contains("sys2"), "{}", msg),
+            _ => panic!("should reject ca report with two sys inputs!"),
+        }
+    }
+
+    #[test]
+    fn should_reject_unknown_or_circular_inputs() {
+        let unknown = Config::parse(&REPORTS.replace(r#"["sys"]"#, r#"["nothing"]"#)).unwrap();
+        assert!(unknown.ordered_reports().is_err());
+
+        let circular = REPORTS.replace(
+            r#"cache = "sys-items.json""#,
+            r#"cache = "sys-items.json"
+        inputs = ["ca"]"#,
+        );
+        let circular = Config::parse(&circular).unwrap();
+        assert!(circular.ordered_reports().is_err());
+    }
+
+    #[test]
+    fn should_isolate_caches_and_history() {
+        let mut config = Config::parse(REPORTS).unwrap();
+        config.history.database = Some("history.db".to_string());
+        let config = config.isolated("replayed");
+        assert_eq!(
+            Path::new(&config.reports[0].cache),
+            Path::new("replayed").join("ca-items.json")
+        );
+        assert_eq!(config.history.database, None);
+    }
+
+    #[test]
+    fn should_reject_shared_cache_or_output() {
+        let cache = Config::parse(&REPORTS.replace("fs2-items.json", "sys-items.json")).unwrap();
+        match cache.ordered_reports() {
+            Err(ConfigError::Invalid(msg)) => assert!(msg.contains("cache"), "{}", msg),
+            _ => panic!("should reject reports of the same cache!"),
+        }
+
+        let second = r#"
+        [[report]]
+        name = "sys2"
+        checker = "sys"
+        jql = "issuetype = \"Customer Feature\""
+        cache = "sys2-items.json"
+        "#;
+        let output = Config::parse(&(REPORTS.to_string() + second)).unwrap();
+        assert!(output.ordered_reports().is_err());
+        let apart = second.to_string() + "output_dir = \"sys2\"";
+        let apart = Config::parse(&(REPORTS.to_string() + &apart)).unwrap();
+        assert!(apart.ordered_reports().is_ok());
+    }
+
+    #[test]
+    fn should_parse_dynamic_report_with_schema() {
+        let config = Config::parse(
+            r#"
+            [[schema]]
+            name = "feature"
+            fields = [
+                { name = "feature_id", field = "Feature ID" },
+                { name = "efforts", field = "timeoriginalestimate", kind = "hours" },
+            ]
+
+            [[report]]
+            name = "features"
+            checker = "dynamic"
+            jql = "project = FPB"
+            cache = "features.json"
+            schema = "feature"
+        "#,
+        )
+        .unwrap();
+        let report = &config.ordered_reports().unwrap()[0];
+        let schema = config.get_schema(report).unwrap();
+        assert_eq!(schema.fields[0].kind, FieldKind::String);
+        assert_eq!(schema.fields[1].kind, FieldKind::Hours);
+
+        let mut unknown = config.clone();
+        unknown.reports[0].schema = Some("nothing".to_string());
+        assert!(unknown.ordered_reports().is_err());
+    }
+
+    #[test]
+    fn should_report_invalid_toml() {
+        match Config::parse("[jira\n") {
+            Err(ConfigError::Toml(_)) => {}
+            _ => panic!("should give toml error!"),
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use hyper::header::HeaderMap;
+use hyper::StatusCode;
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt;
+use std::io;
+
+use crate::config::ConfigError;
+use crate::text::get_summary;
+
+//jira tells the reason of a login denied by too many failures in this header
+const DENIED_REASON: &str = "X-Authentication-Denied-Reason";
+
+//all failures of the library, so that callers decide how to handle them
+#[derive(Debug)]
+pub enum Error {
+    //invalid settings or report pipelines
+    Config(ConfigError),
+
+    //unable to setup the connection, e.g. bad proxy url or CA bundle
+    Setup(String),
+
+    //unable to talk with the server at all
+    Network(hyper::Error),
+
+    //server answered with an unexpected status
+    Http {
+        status: StatusCode,
+        errors: JiraErrors,
+    },
+
+    //login rejected or no permission, captcha means the account is locked
+    // until someone logs in by browser
+    Denied {
+        status: StatusCode,
+        captcha: bool,
+        errors: JiraErrors,
+    },
+
+    //search rejected by the server, normally a syntax error in jql
+    Jql(JiraErrors),
+
+    //response not in expected format, context is the text around the error
+    Json {
+        context: String,
+        source: serde_json::Error,
+    },
+
+    //local cache not readable or writable
+    Cache {
+        path: String,
+        source: io::Error,
+    },
+
+    //report file not writable
+    Report {
+        path: String,
+        source: io::Error,
+    },
+
+    //credentials unusable or rejected
+    Auth(String),
+
+    //field not found by its name in the catalogue of the server
+    Field(String),
+
+    //record or replay of requests failed, e.g. nothing recorded for a request
+    Cassette(String),
+
+    //remaining pages of a search which failed permanently, others are collected
+    Pages(Vec<PageFailure>),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+//error payload of jira REST api
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct JiraErrors {
+    #[serde(rename = "errorMessages")]
+    pub error_messages: Vec<String>,
+
+    //messages by field name
+    pub errors: BTreeMap<String, String>,
+}
+
+impl JiraErrors {
+    //payload from the body, or the body itself if it's not given in json
+    pub fn parse(body: &str) -> JiraErrors {
+        match serde_json::from_str::<JiraErrors>(body) {
+            Ok(errors) => errors,
+            Err(_) => {
+                let body = get_summary(body, 200);
+                JiraErrors {
+                    error_messages: if body.is_empty() {
+                        Vec::new()
+                    } else {
+                        vec![body]
+                    },
+                    errors: BTreeMap::new(),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for JiraErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let messages: Vec<String> = self
+            .error_messages
+            .iter()
+            .cloned()
+            .chain(self.errors.iter().map(|(k, v)| format!("{}: {}", k, v)))
+            .collect();
+        if messages.is_empty() {
+            write!(f, "no details given")
+        } else {
+            write!(f, "{}", messages.join("; "))
+        }
+    }
+}
+
+impl Error {
+    //classify a non successful response by its status
+    pub fn from_response(status: StatusCode, headers: &HeaderMap, body: &str) -> Error {
+        let errors = JiraErrors::parse(body);
+        match status {
+            StatusCode::BAD_REQUEST => Error::Jql(errors),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                let captcha = headers
+                    .get(DENIED_REASON)
+                    .and_then(|value| value.to_str().ok())
+                    .is_some_and(|value| value.contains("CAPTCHA"));
+                Error::Denied {
+                    status,
+                    captcha,
+                    errors,
+                }
+            }
+            _ => Error::Http { status, errors },
+        }
+    }
+}
+
+//a page which could not be fetched even after retries
+#[derive(Debug)]
+pub struct PageFailure {
+    pub start_at: usize,
+    pub attempts: u32,
+    pub error: Error,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Config(err) => write!(f, "invalid config: {}", err),
+            Error::Setup(reason) => write!(f, "unable to setup connection: {}", reason),
+            Error::Network(err) => write!(f, "network failure: {}", err),
+            Error::Http { status, errors } => write!(f, "HTTP {}: {}", status, errors),
+            Error::Denied { captcha: true, .. } => write!(
+                f,
+                "login locked by CAPTCHA challenge, sign in once by browser to unlock it"
+            ),
+            Error::Denied { status, errors, .. } => {
+                write!(f, "access denied by HTTP {}: {}", status, errors)
+            }
+            Error::Jql(errors) => write!(f, "search rejected: {}", errors),
+            Error::Json { source, .. } => write!(f, "invalid json response: {}", source),
+            Error::Cache { path, source } => write!(f, "cache {} unusable: {}", path, source),
+            Error::Report { path, source } => write!(f, "unable to write {}: {}", path, source),
+            Error::Auth(reason) => write!(f, "authentication failed: {}", reason),
+            Error::Field(reason) => write!(f, "unknown field: {}", reason),
+            Error::Cassette(reason) => write!(f, "cassette failure: {}", reason),
+            Error::Pages(pages) => {
+                let starts: Vec<String> = pages.iter().map(|it| it.start_at.to_string()).collect();
+                write!(f, "pages starting at {} failed", starts.join(","))?;
+                match pages.first() {
+                    Some(page) => write!(f, ", first by: {}", page.error),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Network(err) => Some(err),
+            Error::Json { source, .. } => Some(source),
+            Error::Cache { source, .. } | Error::Report { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<ConfigError> for Error {
+    fn from(err: ConfigError) -> Self {
+        Error::Config(err)
+    }
+}
+
+impl From<hyper::Error> for Error {
+    fn from(err: hyper::Error) -> Self {
+        Error::Network(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_describe_failed_pages() {
+        let failure = |start_at| PageFailure {
+            start_at,
+            attempts: 5,
+            error: Error::Http {
+                status: StatusCode::BAD_GATEWAY,
+                errors: JiraErrors::default(),
+            },
+        };
+        let err = Error::Pages(vec![failure(100), failure(300)]);
+        assert_eq!(
+            err.to_string(),
+            "pages starting at 100,300 failed, first by: HTTP 502 Bad Gateway: no details given"
+        );
+    }
+
+    #[test]
+    fn should_parse_jira_error_payload() {
+        let errors = JiraErrors::parse(
+            r#"{"errorMessages": ["Field 'foo' does not exist."], "errors": {"jql": "bad"}}"#,
+        );
+        assert_eq!(errors.error_messages, vec!["Field 'foo' does not exist."]);
+        assert_eq!(errors.to_string(), "Field 'foo' does not exist.; jql: bad");
+
+        let errors = JiraErrors::parse("<html>Bad Gateway</html>");
+        assert_eq!(errors.error_messages, vec!["Bad Gateway"]);
+        let errors = JiraErrors::parse(
+            "<!DOCTYPE html>\n<html>\n<head><title>502 Proxy Error</title>\n\
+             <style type=\"text/css\">h1 { color: red; }</style></head>\n\
+             <body><h1>Proxy Error</h1>\n<p>The proxy server received\n  an invalid \
+             response from an upstream server.</p></body></html>",
+        );
+        assert_eq!(
+            errors.error_messages,
+            vec![
+                "502 Proxy Error Proxy Error The proxy server received an invalid \
+                 response from an upstream server."
+            ]
+        );
+        let long = format!("first line\n{}", "x".repeat(300));
+        assert_eq!(JiraErrors::parse(&long).to_string().chars().count(), 200);
+        assert_eq!(JiraErrors::parse(""), JiraErrors::default());
+    }
+
+    #[test]
+    fn should_classify_responses_by_status() {
+        let mut headers = HeaderMap::new();
+        let body = r#"{"errorMessages": ["The value 'X' does not exist."]}"#;
+        match Error::from_response(StatusCode::BAD_REQUEST, &headers, body) {
+            Error::Jql(errors) => assert_eq!(errors.error_messages.len(), 1),
+            other => panic!("not a jql error: {}", other),
+        }
+        match Error::from_response(StatusCode::UNAUTHORIZED, &headers, "") {
+            Error::Denied { captcha: false, .. } => {}
+            other => panic!("not denied: {}", other),
+        }
+        match Error::from_response(StatusCode::BAD_GATEWAY, &headers, "") {
+            Error::Http { .. } => {}
+            other => panic!("not a http error: {}", other),
+        }
+
+        headers.insert(
+            DENIED_REASON,
+            "CAPTCHA_CHALLENGE; login-url=x".parse().unwrap(),
+        );
+        match Error::from_response(StatusCode::FORBIDDEN, &headers, "") {
+            Error::Denied { captcha: true, .. } => {}
+            other => panic!("captcha not detected: {}", other),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use hyper::header::{HeaderName, AUTHORIZATION, COOKIE};
+use typed_headers::Credentials;
+
+use crate::error::{Error, Result};
+
+//how requests are authenticated, a session has to login before first request
+#[derive(Debug)]
+pub enum Auth {
+    //nothing sent, e.g. public servers or replaying recorded responses
+    Anonymous,
+    Basic(Credentials),
+    Bearer(String),
+    Session {
+        username: String,
+        password: String,
+        cookie: Option<String>,
+    },
+}
+
+#[derive(Serialize)]
+struct SessionLogin<'a> {
+    username: &'a str,
+    password: &'a str,
+}
+
+//session created by /rest/auth/1/session
+#[derive(Deserialize)]
+struct SessionInfo {
+    session: SessionCookie,
+}
+
+#[derive(Deserialize)]
+struct SessionCookie {
+    name: String,
+    value: String,
+}
+
+impl Auth {
+    pub fn basic(username: &str, password: &str) -> Result<Auth> {
+        Credentials::basic(username, password)
+            .map(Auth::Basic)
+            .map_err(|e| Error::Auth(format!("invalid credentials: {}", e)))
+    }
+
+    pub fn bearer(token: &str) -> Auth {
+        Auth::Bearer(token.to_string())
+    }
+
+    pub fn session(username: &str, password: &str) -> Auth {
+        Auth::Session {
+            username: username.to_string(),
+            password: password.to_string(),
+            cookie: None,
+        }
+    }
+
+    //header added to every request, none for a session not yet created
+    pub fn get_header(&self) -> Option<(HeaderName, String)> {
+        match self {
+            Auth::Anonymous => None,
+            Auth::Basic(credentials) => Some((AUTHORIZATION, credentials.to_string())),
+            Auth::Bearer(token) => Some((AUTHORIZATION, format!("Bearer {}", token))),
+            Auth::Session { cookie, .. } => cookie.clone().map(|it| (COOKIE, it)),
+        }
+    }
+
+    //body to create a session, only if it's not created yet
+    pub fn get_login_body(&self) -> Option<String> {
+        match self {
+            Auth::Session {
+                username,
+                password,
+                cookie: None,
+            } => serde_json::to_string(&SessionLogin { username, password }).ok(),
+            _ => None,
+        }
+    }
+
+    //keep the session cookie given by login response
+    pub fn set_session(&mut self, body: &str) -> Result<()> {
+        let info: SessionInfo = serde_json::from_str(body).map_err(|e| Error::Json {
+            context: "session login".to_string(),
+            source: e,
+        })?;
+        if let Auth::Session { cookie, .. } = self {
+            *cookie = Some(format!("{}={}", info.session.name, info.session.value));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_give_header_by_method() {
+        let basic = Auth::basic("Rust", "Rocks!").unwrap();
+        assert_eq!(
+            basic.get_header(),
+            Some((AUTHORIZATION, "Basic UnVzdDpSb2NrcyE=".to_string()))
+        );
+        let bearer = Auth::bearer("token");
+        assert_eq!(
+            bearer.get_header(),
+            Some((AUTHORIZATION, "Bearer token".to_string()))
+        );
+        assert_eq!(bearer.get_login_body(), None);
+    }
+
+    #[test]
+    fn should_login_session_once() {
+        let mut session = Auth::session("Rust", "Rocks!");
+        assert_eq!(session.get_header(), None);
+        let body: serde_json::Value =
+            serde_json::from_str(&session.get_login_body().unwrap()).unwrap();
+        assert_eq!(body["username"], "Rust");
+
+        let response = r#"{"session": {"name": "JSESSIONID", "value": "12345"},
+            "loginInfo": {"failedLoginCount": 1}}"#;
+        session.set_session(response).unwrap();
+        assert_eq!(
+            session.get_header(),
+            Some((COOKIE, "JSESSIONID=12345".to_string()))
+        );
+        assert_eq!(session.get_login_body(), None);
+        assert!(session.set_session("<html/>").is_err());
+    }
+}
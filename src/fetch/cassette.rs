@@ -0,0 +1,211 @@
+use hyper::header::{HeaderMap, HeaderName, HeaderValue};
+use hyper::StatusCode;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use super::fetcher::Response;
+use crate::checkers::records::get_timestamp;
+use crate::error::{Error, Result};
+
+//a recorded request with its response, one json object per line in cassette
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Interaction {
+    method: String,
+    uri: String,
+    request: String,
+    status: u16,
+    headers: Vec<(String, String)>,
+    response: String,
+
+    //when the recording started, absent in cassettes of older versions
+    #[serde(default)]
+    time: u64,
+}
+
+type RequestKey = (String, String, String);
+
+//only headers needed to replay are kept, others like set-cookie give away the
+// session to anyone a cassette is shared with
+const KEPT_HEADERS: [&str; 3] = [
+    "content-type",
+    "retry-after",
+    "x-authentication-denied-reason",
+];
+
+//requests and responses saved to a file, or served from it without network,
+// along with the time of recording which a replay takes as its current time
+#[derive(Debug)]
+pub enum Cassette {
+    Record(RefCell<File>, u64),
+
+    //responses of the same request are given in recorded order, e.g. a
+    // failure before its successful retry
+    Replay(
+        RefCell<HashMap<RequestKey, VecDeque<Interaction>>>,
+        Option<u64>,
+    ),
+}
+
+impl Cassette {
+    pub fn record(path: &Path) -> Result<Cassette> {
+        info!("Recording requests to {}", path.display());
+        File::create(path)
+            .map(|file| Cassette::Record(RefCell::new(file), get_timestamp()))
+            .map_err(|e| Error::Cassette(format!("unable to create {}: {}", path.display(), e)))
+    }
+
+    pub fn replay(path: &Path) -> Result<Cassette> {
+        info!("Replaying requests from {}", path.display());
+        let content = fs::read_to_string(path)
+            .map_err(|e| Error::Cassette(format!("unable to read {}: {}", path.display(), e)))?;
+        Cassette::parse(&content)
+            .map_err(|e| Error::Cassette(format!("invalid cassette {}: {}", path.display(), e)))
+    }
+
+    fn parse(content: &str) -> serde_json::Result<Cassette> {
+        let mut interactions: HashMap<RequestKey, VecDeque<Interaction>> = HashMap::new();
+        let mut time = None;
+        for line in content.lines().filter(|it| !it.trim().is_empty()) {
+            let it: Interaction = serde_json::from_str(line)?;
+            time = time.max(Some(it.time).filter(|it| *it > 0));
+            let key = (it.method.clone(), it.uri.clone(), it.request.clone());
+            interactions.entry(key).or_default().push_back(it);
+        }
+        Ok(Cassette::Replay(RefCell::new(interactions), time))
+    }
+
+    pub fn is_replay(&self) -> bool {
+        match self {
+            Cassette::Replay(..) => true,
+            Cassette::Record(..) => false,
+        }
+    }
+
+    //time of recording, None for cassettes recorded without it
+    pub fn get_timestamp(&self) -> Option<u64> {
+        match self {
+            Cassette::Record(_, time) => Some(*time),
+            Cassette::Replay(_, time) => *time,
+        }
+    }
+
+    pub fn save(&self, method: &str, uri: &str, request: &str, response: &Response) -> Result<()> {
+        let (file, time) = match self {
+            Cassette::Record(file, time) => (file, *time),
+            Cassette::Replay(..) => return Ok(()),
+        };
+        let interaction = Interaction {
+            method: method.to_string(),
+            uri: uri.to_string(),
+            request: request.to_string(),
+            status: response.status.as_u16(),
+            headers: response
+                .headers
+                .iter()
+                .filter(|(k, _)| KEPT_HEADERS.contains(&k.as_str()))
+                .filter_map(|(k, v)| v.to_str().ok().map(|v| (k.to_string(), v.to_string())))
+                .collect(),
+            response: response.body.clone(),
+            time,
+        };
+        let line = serde_json::to_string(&interaction).expect("Interaction is serializable");
+        writeln!(file.borrow_mut(), "{}", line)
+            .map_err(|e| Error::Cassette(format!("unable to record: {}", e)))
+    }
+
+    pub fn play(&self, method: &str, uri: &str, request: &str) -> Result<Response> {
+        let interactions = match self {
+            Cassette::Replay(interactions, _) => interactions,
+            Cassette::Record(..) => return Err(Error::Cassette("not in replay mode".to_string())),
+        };
+        let key = (method.to_string(), uri.to_string(), request.to_string());
+        let it = interactions
+            .borrow_mut()
+            .get_mut(&key)
+            .and_then(|it| it.pop_front())
+            .ok_or_else(|| {
+                Error::Cassette(format!(
+                    "no recorded response for {} {} {}",
+                    method, uri, request
+                ))
+            })?;
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &it.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.append(name, value);
+            }
+        }
+        Ok(Response {
+            status: StatusCode::from_u16(it.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            headers,
+            body: it.response,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_replay_in_recorded_order() {
+        let path = std::env::temp_dir().join(format!("jira-cassette-{}", std::process::id()));
+        let cassette = Cassette::record(&path).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("1"));
+        headers.insert("set-cookie", HeaderValue::from_static("JSESSIONID=secret"));
+        let throttled = Response {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            headers,
+            body: String::new(),
+        };
+        let done = Response {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body: "{}".to_string(),
+        };
+        cassette
+            .save("POST", "http://x/search", "{0}", &throttled)
+            .unwrap();
+        cassette
+            .save("POST", "http://x/search", "{0}", &done)
+            .unwrap();
+        let recorded = cassette.get_timestamp();
+        drop(cassette);
+
+        let cassette = Cassette::replay(&path).unwrap();
+        assert!(cassette.is_replay());
+        assert_eq!(cassette.get_timestamp(), recorded);
+        let first = cassette.play("POST", "http://x/search", "{0}").unwrap();
+        assert_eq!(first.status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(first.headers["retry-after"], "1");
+        assert!(!first.headers.contains_key("set-cookie"));
+        assert!(!fs::read_to_string(&path).unwrap().contains("secret"));
+        let second = cassette.play("POST", "http://x/search", "{0}").unwrap();
+        assert_eq!(
+            (second.status, second.body.as_str()),
+            (StatusCode::OK, "{}")
+        );
+        assert!(cassette.play("POST", "http://x/search", "{0}").is_err());
+        assert!(cassette.play("POST", "http://x/search", "{100}").is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn should_replay_cassette_without_time() {
+        let line = r#"{"method":"GET","uri":"http://x/field","request":"","status":200,"headers":[],"response":"[]"}"#;
+        let cassette = Cassette::parse(line).unwrap();
+        assert_eq!(cassette.get_timestamp(), None);
+        assert_eq!(
+            cassette.play("GET", "http://x/field", "").unwrap().body,
+            "[]"
+        );
+    }
+}
@@ -1,14 +1,24 @@
-use futures::future::{ok, Future};
+use futures::future::{err, result, Future};
 use futures::Stream;
 
+use super::auth::Auth;
+use super::cassette::Cassette;
+use crate::checkers::records::get_timestamp;
+use crate::config::{Config, ProxyConfig};
+use crate::error::{Error, Result};
+use crate::query::field::FieldCatalog;
 use hyper::client::HttpConnector;
-use hyper::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, USER_AGENT};
+use hyper::client::ResponseFuture;
+use hyper::header::{HeaderMap, ACCEPT, CONTENT_TYPE, USER_AGENT};
+use hyper::StatusCode;
 use hyper::{Body, Chunk, Client, Method, Request, Uri};
-use hyper::{Error, StatusCode};
 use hyper_proxy::{Intercept, Proxy, ProxyConnector};
+use hyper_tls::HttpsConnector;
+use native_tls::{Certificate, TlsConnector};
+use std::fs;
+use std::io;
 use std::rc::Rc;
 use tokio_core::reactor::Core;
-use typed_headers::Credentials;
 
 #[derive(Debug)]
 pub struct RequestInfo {
@@ -18,95 +28,171 @@ pub struct RequestInfo {
 }
 
 impl RequestInfo {
-    pub fn post(uri: &str, body: &str) -> RequestInfo {
-        RequestInfo {
+    pub fn post(uri: &str, body: &str) -> Result<RequestInfo> {
+        Ok(RequestInfo {
             method: Method::POST,
-            uri: uri.parse().unwrap(),
+            uri: parse_uri(uri)?,
             body: body.to_string(),
-        }
+        })
     }
 
-    pub fn get(uri: &str) -> RequestInfo {
-        RequestInfo {
+    pub fn get(uri: &str) -> Result<RequestInfo> {
+        Ok(RequestInfo {
             method: Method::GET,
-            uri: uri.parse().unwrap(),
+            uri: parse_uri(uri)?,
             body: String::from("{}"),
+        })
+    }
+}
+
+//connection to jira, either through configured proxy or directly, TLS is
+// done by the https connector for direct mode and by the tunnel when proxied
+#[derive(Debug)]
+enum HttpClient {
+    Direct(Client<HttpsConnector<HttpConnector>>),
+    Proxied(Client<ProxyConnector<HttpConnector>>),
+}
+
+impl HttpClient {
+    fn request(&self, request: Request<Body>) -> ResponseFuture {
+        match self {
+            HttpClient::Direct(client) => client.request(request),
+            HttpClient::Proxied(client) => client.request(request),
         }
     }
 }
 
+//a complete response with its body
+#[derive(Debug)]
+pub struct Response {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
 #[derive(Debug)]
 pub struct Fetcher {
-    login: Rc<Credentials>,
-    client: Option<Client<ProxyConnector<HttpConnector>>>,
+    auth: Auth,
+    client: HttpClient,
+    session_uri: String,
+    cassette: Option<Rc<Cassette>>,
+
+    //fetched once by the first search
+    fields: Option<Rc<FieldCatalog>>,
 }
 
 impl Fetcher {
-    pub fn new(login: Rc<Credentials>) -> Fetcher {
-        Fetcher {
-            login: login.clone(),
-            client: None,
-        }
+    pub fn new(auth: Auth, config: &Config) -> Result<Fetcher> {
+        Ok(Fetcher {
+            auth,
+            client: create_client(&config.proxy, config.jira.ca_bundle.as_ref())?,
+            session_uri: config.session_uri(),
+            cassette: None,
+            fields: None,
+        })
     }
 
-    //perfor a single qiuery with given request information and parser function
-    pub fn query_with<P>(
-        &mut self,
-        req: RequestInfo,
-        mut core: &mut Core,
-        result_parser: Option<P>,
-    ) -> impl Future<Item = String, Error = Error>
-    where
-        P: FnOnce(&str, StatusCode) -> (),
-    {
-        self.prepare_proxied_client_if_not_created(&mut core);
-        let request = self.build_request_and_add_headers(req);
+    //record all queries into the cassette, or answer them from it
+    pub fn with_cassette(mut self, cassette: Cassette) -> Fetcher {
+        self.cassette = Some(Rc::new(cassette));
+        self
+    }
 
-        //perform request now
-        self.client
+    //current time of the queries, a whole recording or replay takes the time
+    // it was recorded so that its results are reproducible
+    pub fn get_timestamp(&self) -> u64 {
+        self.cassette
             .as_ref()
-            .unwrap()
-            .request(request)
-            .and_then(|res| {
-                info!("Received response now! {}", res.status());
-                let status = ok::<StatusCode, Error>(res.status());
-                res.into_body().concat2().join(status)
-            })
-            .map(move |result: (Chunk, StatusCode)| {
-                use std::str::from_utf8;
-                let (body, code) = result;
-                let body_str: &str = match code {
-                    StatusCode::OK => from_utf8(&body).unwrap(),
-                    _ => "invalid, don't parse",
-                };
-
-                if let Some(parser) = result_parser {
-                    parser(&body_str, code);
-                }
-
-                "performed".to_string()
-            })
+            .and_then(|it| it.get_timestamp())
+            .unwrap_or_else(get_timestamp)
     }
 
-    fn prepare_proxied_client_if_not_created(&mut self, _core: &mut Core) {
-        if self.client.is_some() {
-            return
+    //create the login session if needed, before any other request
+    pub fn authenticate(&mut self, core: &mut Core) -> Result<()> {
+        let body = match self.auth.get_login_body() {
+            Some(body) => body,
+            None => return Ok(()),
+        };
+        if self.cassette.as_ref().is_some_and(|it| it.is_replay()) {
+            return Ok(());
         }
+        //never recorded, the request has the password and response the cookie
+        info!("Creating login session at {}", self.session_uri);
+        let response = core.run(self.send(RequestInfo::post(&self.session_uri, &body)?))?;
+        if !response.status.is_success() {
+            let status = response.status;
+            return Err(Error::from_response(
+                status,
+                &response.headers,
+                &response.body,
+            ));
+        }
+        self.auth.set_session(&response.body)
+    }
 
-        info!("Creating client connection since not exist yet!");
-        //let handle = core.handle();
-        let proxy = {
-            let proxy_uri = "http://10.144.1.10:8080".parse().unwrap();
-            let mut proxy = Proxy::new(Intercept::All, proxy_uri);
-            proxy.set_authorization((*self.login).clone());
+    //field catalogue of the server, fetched on first use only
+    pub fn get_field_catalog(&mut self, core: &mut Core, uri: &str) -> Result<Rc<FieldCatalog>> {
+        if let Some(fields) = &self.fields {
+            return Ok(fields.clone());
+        }
+        self.authenticate(core)?;
+        info!("Fetching field catalogue from {}", uri);
+        let response = core.run(self.query_with(RequestInfo::get(uri)?))?;
+        if !response.status.is_success() {
+            return Err(Error::from_response(
+                response.status,
+                &response.headers,
+                &response.body,
+            ));
+        }
+        let fields = Rc::new(FieldCatalog::parse(&response.body)?);
+        self.fields = Some(fields.clone());
+        Ok(fields)
+    }
 
-            let connector = HttpConnector::new(4);
-            ProxyConnector::from_proxy(connector, proxy).unwrap()
+    //perform a single query with given request information, the returned future
+    // doesn't borrow the fetcher so that many of them can run at the same time
+    pub fn query_with(&self, req: RequestInfo) -> Box<dyn Future<Item = Response, Error = Error>> {
+        let cassette = match &self.cassette {
+            Some(cassette) => cassette.clone(),
+            None => return self.send(req),
         };
-        self.client = Some(Client::builder().build(proxy))
+        let (method, uri, body) = (
+            req.method.to_string(),
+            req.uri.to_string(),
+            req.body.clone(),
+        );
+        if cassette.is_replay() {
+            info!("Replay for {}", uri);
+            return Box::new(result(cassette.play(&method, &uri, &body)));
+        }
+        Box::new(self.send(req).and_then(move |response| {
+            cassette
+                .save(&method, &uri, &body, &response)
+                .map(|_| response)
+        }))
     }
 
-    fn build_request_and_add_headers(&self, req: RequestInfo) -> Request<Body> {
+    fn send(&self, req: RequestInfo) -> Box<dyn Future<Item = Response, Error = Error>> {
+        let request = match self.build_request_and_add_headers(req) {
+            Ok(request) => request,
+            Err(e) => return Box::new(err(e)),
+        };
+
+        //perform request now
+        let response = self.client.request(request).and_then(|res| {
+            info!("Received response now! {}", res.status());
+            let (parts, body) = res.into_parts();
+            body.concat2().map(move |body: Chunk| Response {
+                status: parts.status,
+                headers: parts.headers,
+                body: String::from_utf8_lossy(&body).into_owned(),
+            })
+        });
+        Box::new(response.map_err(Error::Network))
+    }
+
+    fn build_request_and_add_headers(&self, req: RequestInfo) -> Result<Request<Body>> {
         info!("Request for {}", &req.uri);
 
         let mut builder = Request::builder();
@@ -114,16 +200,111 @@ impl Fetcher {
 
         builder.header(USER_AGENT, "MyScript");
         builder.header(ACCEPT, "application/json");
-        builder.header(AUTHORIZATION, format!("{}", (*self.login)));
+        if let Some((name, value)) = self.auth.get_header() {
+            builder.header(name, value);
+        }
         builder.header(CONTENT_TYPE, "application/json");
 
-        let request = builder.body(Body::from(req.body)).unwrap();
+        let request = builder
+            .body(Body::from(req.body))
+            .map_err(|e| Error::Auth(format!("invalid request header: {}", e)))?;
 
         //check headers inside if we are correct?
         for hdr in request.headers() {
             debug!("=== header: {:?}", hdr);
         }
 
-        request
+        Ok(request)
+    }
+}
+
+fn parse_uri(uri: &str) -> Result<Uri> {
+    uri.parse()
+        .map_err(|e| Error::Setup(format!("invalid uri {}: {}", uri, e)))
+}
+
+fn create_client(proxy_config: &ProxyConfig, ca_bundle: Option<&String>) -> Result<HttpClient> {
+    info!("Creating client connection now!");
+    let tls = build_tls_connector(ca_bundle)
+        .map_err(|e| Error::Setup(format!("unable to setup TLS: {}", e)))?;
+    let mut connector = HttpConnector::new(4);
+    match proxy_config.get_url() {
+        Some(url) => {
+            info!("Connecting via proxy {}", url);
+            let mut proxy = Proxy::new(Intercept::All, parse_uri(url)?);
+            if let Some(credentials) = proxy_config.get_credentials() {
+                proxy.set_authorization(credentials);
+            }
+            let mut proxy = ProxyConnector::from_proxy_unsecured(connector, proxy);
+            proxy.set_tls(Some(tls));
+            Ok(HttpClient::Proxied(Client::builder().build(proxy)))
+        }
+        None => {
+            info!("Connecting directly without proxy");
+            connector.enforce_http(false);
+            let https = HttpsConnector::from((connector, tls));
+            Ok(HttpClient::Direct(Client::builder().build(https)))
+        }
+    }
+}
+
+const PEM_BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+const PEM_END: &str = "-----END CERTIFICATE-----";
+
+//every certificate block of a PEM bundle, as a chain usually has several
+fn split_pem(pem: &str) -> Vec<&str> {
+    let mut blocks = Vec::new();
+    let mut rest = pem;
+    while let Some(begin) = rest.find(PEM_BEGIN) {
+        let end = match rest[begin..].find(PEM_END) {
+            Some(end) => begin + end + PEM_END.len(),
+            None => break,
+        };
+        blocks.push(&rest[begin..end]);
+        rest = &rest[end..];
+    }
+    blocks
+}
+
+//TLS with system trusted roots, plus the given PEM bundle if any (e.g. corporate CA)
+fn build_tls_connector(ca_bundle: Option<&String>) -> io::Result<TlsConnector> {
+    let mut builder = TlsConnector::builder();
+    if let Some(path) = ca_bundle {
+        let pem = fs::read_to_string(path)?;
+        let blocks = split_pem(&pem);
+        if blocks.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("no certificate found in {}", path),
+            ));
+        }
+        info!(
+            "Trusting {} extra CA certificates from {}",
+            blocks.len(),
+            path
+        );
+        for block in blocks {
+            let cert = Certificate::from_pem(block.as_bytes())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            builder.add_root_certificate(cert);
+        }
+    }
+    builder.build().map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_split_every_certificate_of_bundle() {
+        let pem = format!(
+            "subject=root\n{}\nAAAA\n{}\nsubject=intermediate\n{}\nBBBB\n{}\n{}\nbroken",
+            PEM_BEGIN, PEM_END, PEM_BEGIN, PEM_END, PEM_BEGIN
+        );
+        let blocks = split_pem(&pem);
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks[1].starts_with(PEM_BEGIN) && blocks[1].contains("BBBB"));
+        assert!(blocks[1].ends_with(PEM_END));
     }
 }
@@ -2,13 +2,23 @@ extern crate hex;
 extern crate hyper;
 extern crate rpassword;
 
+use super::auth::Auth;
+use super::netrc::{self, NetrcEntry};
+use crate::config::{AuthMethod, JiraConfig};
+use crate::error::{Error, Result};
 use hex::{decode, encode};
 use rpassword::prompt_password_stdout;
-use std::fs;
-use std::io::{stdin, stdout, Write};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, stdin, stdout, IsTerminal, Write};
+use std::path::Path;
 use typed_headers::Credentials;
 
-const CRED_FILE: &str = ".cred.bin";
+//hex encoded file of older versions, only read for migration
+const LEGACY_CRED_FILE: &str = ".cred.bin";
+
+const ENV_USER: &str = "JIRA_USER";
+const ENV_PASSWORD: &str = "JIRA_PASSWORD";
 
 #[derive(PartialEq, Debug)]
 pub struct Login {
@@ -16,63 +26,151 @@ pub struct Login {
     password: String,
 }
 
-impl Default for Login {
-    fn default() -> Self {
-        Login::new()
+impl Login {
+    //login details by the first source which gives them: command line, then
+    // environment, credential file or legacy file which is migrated, then
+    // terminal if attached, prompted ones are saved for next time; only the
+    // missing ones are taken from later sources, and bearer needs no username
+    pub fn new(
+        config: &JiraConfig,
+        username: Option<String>,
+        password: Option<String>,
+    ) -> Result<Login> {
+        let username = username.or_else(|| env::var(ENV_USER).ok());
+        let password = password.or_else(|| env::var(ENV_PASSWORD).ok());
+        let bearer = config.auth == AuthMethod::Bearer;
+        match (&username, &password) {
+            (Some(username), Some(password)) => {
+                info!("Using credentials from command line or environment");
+                return Ok(new_login(username.clone(), password.clone()));
+            }
+            (None, Some(password)) if bearer => {
+                info!("Using token from command line or environment");
+                return Ok(new_login(String::new(), password.clone()));
+            }
+            _ => {}
+        }
+
+        let path = config.get_credential_file();
+        let host = config.get_host();
+        let stored = Login::load_credentials(&path, &host)
+            .or_else(|| Login::migrate_legacy(&path, &host))
+            .filter(|it| username.as_ref().is_none_or(|name| *name == it.username));
+        if let Some(stored) = stored {
+            return Ok(Login {
+                username: stored.username,
+                password: password.unwrap_or(stored.password),
+            });
+        }
+
+        if !stdin().is_terminal() {
+            return Err(Error::Auth(format!(
+                "no credentials for {}, give --user and --password, set {} and {}, \
+                 or add them to {}",
+                host,
+                ENV_USER,
+                ENV_PASSWORD,
+                path.display()
+            )));
+        }
+        let login = Login::create_from_terminal(username, password, bearer)
+            .map_err(|e| Error::Auth(format!("unable to read credentials: {}", e)))?;
+        if let Err(e) = login.save_credentials(&path, &host) {
+            warn!("Unable to save credentials to {}: {}", path.display(), e);
+        }
+        Ok(login)
     }
-}
 
-impl Login {
-    pub fn new() -> Login {
-        match Login::load_credentials() {
-            Some(login) => login,
+    //prompt for the missing ones only
+    fn create_from_terminal(
+        username: Option<String>,
+        password: Option<String>,
+        bearer: bool,
+    ) -> io::Result<Login> {
+        let username = match username {
+            Some(username) => username,
+            None if bearer => String::new(),
             None => {
-                let login = Login::create_from_terminal();
-                login.save_credentials();
-                login
+                print!("Please input your account:");
+                stdout().flush()?;
+                let mut line = String::new();
+                stdin().read_line(&mut line)?;
+                line.trim().to_string()
             }
+        };
+        if username.is_empty() && !bearer {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "empty account"));
         }
+        let password = match password {
+            Some(password) => password,
+            None if bearer => prompt_password_stdout("Please input your token:")?,
+            None => prompt_password_stdout("Please input your password:")?,
+        };
+        Ok(Login { username, password })
     }
 
-    fn create_from_terminal() -> Login {
-        let mut buf1 = String::new();
-        loop {
-            print!("Please input your account:");
-            stdout().flush().unwrap();
-            //username/password have to be 3~20 characters and newline is not needed
-            // also to consider the \r\n extra characters read in by read_line() call
-            if let Ok(name_len @ 5...20) = stdin().read_line(&mut buf1) {
-                let passwd_string = prompt_password_stdout("Please input your password:");
-                if let Ok(passwd_string) = passwd_string {
-                    return Login {
-                        username: buf1[0..name_len - 2].to_string(),
-                        password: passwd_string,
-                    };
-                }
-            }
+    //authentication by given method, password is the token for bearer
+    pub fn to_auth(&self, method: AuthMethod) -> Result<Auth> {
+        match method {
+            AuthMethod::Basic => self.to_basic().map(Auth::Basic),
+            AuthMethod::Bearer => Ok(Auth::bearer(&self.password)),
+            AuthMethod::Session => Ok(Auth::session(&self.username, &self.password)),
         }
     }
 
-    pub fn to_basic(&self) -> Credentials {
+    pub fn to_basic(&self) -> Result<Credentials> {
         //we just show password as a series of stars
         info!(
             "Prepared login details now with user=<{}>, pwd=<{}>",
             self.username,
-            String::from_utf8(vec![42; self.password.len()]).unwrap()
+            "*".repeat(self.password.len())
         );
-        Credentials::basic(&self.username, &self.password).unwrap()
+        Credentials::basic(&self.username, &self.password)
+            .map_err(|e| Error::Auth(format!("invalid credentials: {}", e)))
     }
 
-    pub fn load_credentials() -> Option<Login> {
-        match fs::read(CRED_FILE) {
-            Ok(content) => Login::load_from_vec(content),
+    //entry of given host in netrc style file
+    pub fn load_credentials(path: &Path, host: &str) -> Option<Login> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
             Err(_) => {
-                warn!("Either file not exist or not readalbe:{}", CRED_FILE);
+                warn!("Either file not exist or not readable:{}", path.display());
+                return None;
+            }
+        };
+        check_permissions(path);
+        let entries = netrc::parse(&content);
+        match netrc::find(&entries, host) {
+            Some(entry) => Some(Login {
+                username: entry.login.clone(),
+                password: entry.password.clone(),
+            }),
+            None => {
+                warn!("No entry for {} in {}", host, path.display());
                 None
             }
         }
     }
 
+    //move credentials of the hex encoded legacy file into the credential file
+    fn migrate_legacy(path: &Path, host: &str) -> Option<Login> {
+        let login = Login::load_from_vec(fs::read(LEGACY_CRED_FILE).ok()?)?;
+        match login.save_credentials(path, host) {
+            Ok(()) => {
+                info!(
+                    "Credentials migrated from {} to {}",
+                    LEGACY_CRED_FILE,
+                    path.display()
+                );
+                if let Err(e) = fs::remove_file(LEGACY_CRED_FILE) {
+                    warn!("Unable to remove {}: {}", LEGACY_CRED_FILE, e);
+                }
+            }
+            Err(e) => warn!("Unable to migrate {}: {}", LEGACY_CRED_FILE, e),
+        }
+        Some(login)
+    }
+
     pub fn load_from_vec(content: Vec<u8>) -> Option<Login> {
         if content.len() < 5 {
             error!("content is too short!");
@@ -85,36 +183,44 @@ impl Login {
         }
 
         let sep: usize = content[0] as usize;
-        let v8_slice_to_string = |vec: &[u8]| String::from_utf8(vec.to_vec()).unwrap();
+        let v8_slice_to_string = |vec: &[u8]| String::from_utf8(vec.to_vec()).ok();
         let decode_string =
-            |slice: &[u8]| v8_slice_to_string(&decode(v8_slice_to_string(slice)).unwrap());
+            |slice: &[u8]| v8_slice_to_string(&decode(v8_slice_to_string(slice)?).ok()?);
         if sep < (content.len() - 1) / 2 {
             Some(Login {
-                username: decode_string(&content[1..sep]),
-                password: decode_string(&content[sep..content.len()]),
+                username: decode_string(&content[1..sep])?,
+                password: decode_string(&content[sep..content.len()])?,
             })
         } else {
             warn!(
                 "Invalid credential file = {}, is this first time use?",
-                CRED_FILE
+                LEGACY_CRED_FILE
             );
             None
         }
     }
 
-    pub fn save_credentials(&self) -> Option<String> {
-        let mut contents: Vec<u8> = Vec::new();
-        self.save_to_temp(&mut contents);
-        match fs::write(CRED_FILE, contents) {
-            Ok(()) => {
-                info!("New credentials saved for future use!");
-                None
-            }
-            Err(_) => {
-                error!("Writing into file failed!");
-                Some("Write error".to_string())
-            }
-        }
+    //add or replace entry of given host, the file is only readable by its owner
+    pub fn save_credentials(&self, path: &Path, host: &str) -> io::Result<()> {
+        let mut entries = match fs::read_to_string(path) {
+            Ok(content) => netrc::parse(&content),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        netrc::upsert(
+            &mut entries,
+            NetrcEntry {
+                machine: Some(host.to_string()),
+                login: self.username.clone(),
+                password: self.password.clone(),
+            },
+        );
+        write_private(path, netrc::format(&entries).as_bytes())?;
+        info!(
+            "New credentials saved to {} for future use!",
+            path.display()
+        );
+        Ok(())
     }
 
     pub fn save_to_temp(&self, contents: &mut Vec<u8>) {
@@ -127,3 +233,44 @@ impl Login {
 pub fn new_login(username: String, password: String) -> Login {
     Login { username, password }
 }
+
+#[cfg(unix)]
+fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    //mode above is only applied to new files
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(contents)
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
+    file.write_all(contents)
+}
+
+//others shouldn't be able to read the passwords
+#[cfg(unix)]
+fn check_permissions(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+    if let Ok(meta) = fs::metadata(path) {
+        if meta.permissions().mode() & 0o077 != 0 {
+            warn!(
+                "Credential file {} is accessible by others, run chmod 600 on it!",
+                path.display()
+            );
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_path: &Path) {}
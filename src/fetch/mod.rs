@@ -1,9 +1,15 @@
+pub mod auth;
+pub mod cassette;
 pub mod fetcher;
 pub mod login;
+pub mod netrc;
+pub mod retry;
+pub mod throttle;
 
 #[cfg(test)]
 mod test {
     use super::login::{new_login, Login};
+    use crate::config::AuthMethod;
 
     #[test]
     fn shall_load_cred_from_contents() {
@@ -30,4 +36,68 @@ mod test {
         login.save_to_temp(&mut saved);
         assert_eq!(contents, saved);
     }
+
+    #[test]
+    fn shall_prefer_given_cred() {
+        let config = crate::config::JiraConfig::default();
+        let login = Login::new(
+            &config,
+            Some("Rust".to_string()),
+            Some("Rocks!".to_string()),
+        );
+        assert_eq!(
+            login.unwrap(),
+            new_login("Rust".to_string(), "Rocks!".to_string())
+        );
+    }
+
+    #[test]
+    fn shall_keep_given_password_and_take_missing_user() {
+        let path = std::env::temp_dir().join(format!("jira-given-{}", std::process::id()));
+        let config = crate::config::JiraConfig {
+            credential_file: Some(path.display().to_string()),
+            ..Default::default()
+        };
+        let saved = new_login("Rust".to_string(), "Saved!".to_string());
+        saved.save_credentials(&path, &config.get_host()).unwrap();
+
+        let login = Login::new(&config, None, Some("Rocks!".to_string())).unwrap();
+        assert_eq!(login, new_login("Rust".to_string(), "Rocks!".to_string()));
+        let login = Login::new(&config, Some("Rust".to_string()), None).unwrap();
+        assert_eq!(login, saved);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn shall_give_bearer_token_without_user() {
+        let config = crate::config::JiraConfig {
+            auth: AuthMethod::Bearer,
+            credential_file: Some("/nonexistent/jira-netrc".to_string()),
+            ..Default::default()
+        };
+        let login = Login::new(&config, None, Some("token".to_string())).unwrap();
+        assert_eq!(login, new_login(String::new(), "token".to_string()));
+        assert!(login.to_auth(AuthMethod::Bearer).is_ok());
+    }
+
+    #[test]
+    fn shall_save_cred_privately_by_host() {
+        let path = std::env::temp_dir().join(format!("jira-cred-{}", std::process::id()));
+        let login = new_login("Rust".to_string(), "Rocks!".to_string());
+        login.save_credentials(&path, "jira.example.com").unwrap();
+        let other = new_login("Other".to_string(), "secret".to_string());
+        other.save_credentials(&path, "other.com").unwrap();
+
+        let loaded = Login::load_credentials(&path, "jira.example.com");
+        assert_eq!(loaded, Some(login));
+        assert_eq!(Login::load_credentials(&path, "unknown.com"), None);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
 }
@@ -0,0 +1,208 @@
+//netrc style credential entries, e.g.
+// machine jira.example.com login someone password secret
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetrcEntry {
+    //none for the "default" entry matching any machine
+    pub machine: Option<String>,
+    pub login: String,
+    pub password: String,
+}
+
+//tokens split by whitespace, a double quoted one may have any characters
+// with quotes, backslashes and line breaks escaped by a backslash
+fn tokenize(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(&next) = chars.peek() {
+        if next.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut token = String::new();
+        if next == '"' {
+            chars.next();
+            while let Some(it) = chars.next() {
+                match it {
+                    '"' => break,
+                    '\\' => match chars.next() {
+                        Some('n') => token.push('\n'),
+                        Some('r') => token.push('\r'),
+                        Some('t') => token.push('\t'),
+                        Some(other) => token.push(other),
+                        None => {}
+                    },
+                    _ => token.push(it),
+                }
+            }
+        } else {
+            while let Some(it) = chars.next_if(|it| !it.is_whitespace()) {
+                token.push(it);
+            }
+        }
+        tokens.push(token);
+    }
+    tokens
+}
+
+//token as written, quoted if it wouldn't be read back as it is
+fn quote(token: &str) -> String {
+    let plain = !token.is_empty()
+        && !token.starts_with('#')
+        && !token.contains(|it: char| it.is_whitespace() || it == '"' || it == '\\');
+    if plain {
+        return token.to_string();
+    }
+    let mut quoted = String::from("\"");
+    for it in token.chars() {
+        match it {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            _ => quoted.push(it),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+pub fn parse(content: &str) -> Vec<NetrcEntry> {
+    let mut entries = Vec::new();
+    let mut current: Option<NetrcEntry> = None;
+    let mut tokens = content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .flat_map(tokenize);
+    while let Some(token) = tokens.next() {
+        let start = |machine: Option<&str>| NetrcEntry {
+            machine: machine.map(String::from),
+            login: String::new(),
+            password: String::new(),
+        };
+        match token.as_str() {
+            "machine" => {
+                entries.extend(current.take());
+                current = Some(start(tokens.next().as_deref()));
+            }
+            "default" => {
+                entries.extend(current.take());
+                current = Some(start(None));
+            }
+            "login" => {
+                if let (Some(entry), Some(login)) = (current.as_mut(), tokens.next()) {
+                    entry.login = login;
+                }
+            }
+            "password" => {
+                if let (Some(entry), Some(password)) = (current.as_mut(), tokens.next()) {
+                    entry.password = password;
+                }
+            }
+            //account, macdef and others are not used by us
+            _ => {}
+        }
+    }
+    entries.extend(current);
+    entries
+}
+
+//entry of given machine, the default one if not found
+pub fn find<'a>(entries: &'a [NetrcEntry], host: &str) -> Option<&'a NetrcEntry> {
+    entries
+        .iter()
+        .find(|it| it.machine.as_ref().is_some_and(|m| m == host))
+        .or_else(|| entries.iter().find(|it| it.machine.is_none()))
+}
+
+//replace entry of the same machine, or add it before the default one
+pub fn upsert(entries: &mut Vec<NetrcEntry>, entry: NetrcEntry) {
+    match entries.iter().position(|it| it.machine == entry.machine) {
+        Some(pos) => entries[pos] = entry,
+        None => {
+            let pos = entries
+                .iter()
+                .position(|it| it.machine.is_none())
+                .unwrap_or(entries.len());
+            entries.insert(pos, entry);
+        }
+    }
+}
+
+pub fn format(entries: &[NetrcEntry]) -> String {
+    entries
+        .iter()
+        .map(|it| {
+            let machine = match &it.machine {
+                Some(machine) => format!("machine {}", quote(machine)),
+                None => "default".to_string(),
+            };
+            format!(
+                "{} login {} password {}\n",
+                machine,
+                quote(&it.login),
+                quote(&it.password)
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(machine: Option<&str>, login: &str) -> NetrcEntry {
+        NetrcEntry {
+            machine: machine.map(String::from),
+            login: login.to_string(),
+            password: "secret".to_string(),
+        }
+    }
+
+    #[test]
+    fn should_parse_entries_across_lines() {
+        let entries = parse(
+            "# comment line\n\
+             machine jira.example.com\n  login someone\n  password secret\n\
+             machine other.com login other password secret account x\n\
+             default login anyone password secret\n",
+        );
+        assert_eq!(
+            entries,
+            vec![
+                entry(Some("jira.example.com"), "someone"),
+                entry(Some("other.com"), "other"),
+                entry(None, "anyone"),
+            ]
+        );
+        assert_eq!(find(&entries, "other.com").unwrap().login, "other");
+        assert_eq!(find(&entries, "unknown.com").unwrap().login, "anyone");
+        assert_eq!(find(&entries[..2], "unknown.com"), None);
+    }
+
+    #[test]
+    fn should_replace_same_machine_only() {
+        let mut entries = vec![entry(Some("a.com"), "a"), entry(None, "anyone")];
+        upsert(&mut entries, entry(Some("b.com"), "b"));
+        upsert(&mut entries, entry(Some("a.com"), "new"));
+        assert_eq!(
+            format(&entries),
+            "machine a.com login new password secret\n\
+             machine b.com login b password secret\n\
+             default login anyone password secret\n"
+        );
+        assert_eq!(parse(&format(&entries)), entries);
+    }
+
+    #[test]
+    fn should_keep_passwords_of_any_characters() {
+        let mut entries = vec![entry(Some("a.com"), ""), entry(None, "any one")];
+        entries[0].password = "with space\nline \"quoted\" \\ #x\ttab".to_string();
+        entries[1].password = "#hash".to_string();
+        let content = format(&entries);
+        assert_eq!(content.lines().count(), 2, "{}", content);
+        assert_eq!(parse(&content), entries);
+        assert_eq!(parse("machine a.com login a password \"\"")[0].password, "");
+    }
+}
@@ -0,0 +1,150 @@
+use hyper::header::{HeaderMap, RETRY_AFTER};
+use hyper::StatusCode;
+use rand::Rng;
+use std::cmp::min;
+use std::time::Duration;
+
+use crate::config::SearchConfig;
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    //total attempts including the first one
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &SearchConfig) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: config.max_attempts.max(1),
+            base_delay: Duration::from_millis(config.base_delay_ms),
+            max_delay: Duration::from_millis(config.max_delay_ms),
+        }
+    }
+
+    pub fn can_retry(&self, attempt: u32) -> bool {
+        attempt < self.max_attempts
+    }
+
+    //delay before next attempt, server's Retry-After wins over our own backoff
+    // but is capped by max delay too, so that a server can't stall the run
+    pub fn delay_for(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        match retry_after {
+            Some(delay) => min(delay, self.max_delay),
+            None => {
+                let ceiling = self.backoff_ceiling(attempt);
+                let jitter = rand::thread_rng().gen_range(0, ceiling.as_millis() as u64 + 1);
+                Duration::from_millis(jitter)
+            }
+        }
+    }
+
+    //exponential upper bound for given attempt, starting from 1
+    fn backoff_ceiling(&self, attempt: u32) -> Duration {
+        let factor = 1u32
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        let delay = self
+            .base_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay);
+        min(delay, self.max_delay)
+    }
+}
+
+//whether it's worthwhile to send the same request again
+pub fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+//Retry-After given in seconds on 429 or 503, http-date form is not supported
+pub fn get_retry_after(status: StatusCode, headers: &HeaderMap) -> Option<Duration> {
+    if status != StatusCode::TOO_MANY_REQUESTS && status != StatusCode::SERVICE_UNAVAILABLE {
+        return None;
+    }
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    fn get_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+        }
+    }
+
+    #[test]
+    fn should_grow_backoff_until_max_delay() {
+        let policy = get_policy();
+        let ceilings: Vec<u64> = (1..6)
+            .map(|it| policy.backoff_ceiling(it).as_millis() as u64)
+            .collect();
+        assert_eq!(ceilings, vec![100, 200, 400, 500, 500]);
+        assert_eq!(policy.backoff_ceiling(64), policy.max_delay);
+    }
+
+    #[test]
+    fn should_jitter_within_ceiling() {
+        let policy = get_policy();
+        for attempt in 1..10 {
+            assert!(policy.delay_for(attempt, None) <= policy.backoff_ceiling(attempt));
+        }
+        let server_delay = Some(Duration::from_millis(300));
+        assert_eq!(
+            policy.delay_for(1, server_delay),
+            Duration::from_millis(300)
+        );
+        let server_delay = Some(Duration::from_secs(86400));
+        assert_eq!(policy.delay_for(1, server_delay), policy.max_delay);
+    }
+
+    #[test]
+    fn should_stop_after_max_attempts() {
+        let policy = get_policy();
+        assert!(policy.can_retry(3));
+        assert!(!policy.can_retry(4));
+    }
+
+    #[test]
+    fn should_honor_retry_after_on_throttling_only() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        let expected = Some(Duration::from_secs(7));
+        assert_eq!(
+            get_retry_after(StatusCode::TOO_MANY_REQUESTS, &headers),
+            expected
+        );
+        assert_eq!(
+            get_retry_after(StatusCode::SERVICE_UNAVAILABLE, &headers),
+            expected
+        );
+        assert_eq!(get_retry_after(StatusCode::BAD_GATEWAY, &headers), None);
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(
+            get_retry_after(StatusCode::TOO_MANY_REQUESTS, &headers),
+            None
+        );
+    }
+
+    #[test]
+    fn should_retry_throttled_and_server_errors() {
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable(StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable(StatusCode::BAD_REQUEST));
+        assert!(!is_retryable(StatusCode::UNAUTHORIZED));
+    }
+}
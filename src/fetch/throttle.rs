@@ -0,0 +1,83 @@
+use std::time::{Duration, Instant};
+
+//token bucket shared by all requests of a search, tokens are reserved ahead
+// so every caller knows how long to wait before sending
+#[derive(Debug)]
+pub struct TokenBucket {
+    //tokens added per second, unlimited if not positive
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rate: f64, capacity: usize) -> TokenBucket {
+        let capacity = capacity.max(1) as f64;
+        TokenBucket {
+            rate,
+            capacity,
+            tokens: capacity,
+            last: Instant::now(),
+        }
+    }
+
+    //take one token, giving the delay until it is really available
+    pub fn reserve(&mut self) -> Duration {
+        self.reserve_at(Instant::now())
+    }
+
+    fn reserve_at(&mut self, now: Instant) -> Duration {
+        if self.rate <= 0.0 {
+            return Duration::from_secs(0);
+        }
+        if now > self.last {
+            let elapsed = now.duration_since(self.last).as_secs_f64();
+            self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+            self.last = now;
+        }
+
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::from_secs(0)
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_allow_burst_then_space_requests() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(2.0, 2);
+        bucket.last = now;
+        let delays: Vec<u128> = (0..5).map(|_| bucket.reserve_at(now).as_millis()).collect();
+        assert_eq!(delays, vec![0, 0, 500, 1000, 1500]);
+    }
+
+    #[test]
+    fn should_refill_up_to_capacity() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(1.0, 2);
+        bucket.last = now;
+        bucket.reserve_at(now);
+        bucket.reserve_at(now);
+
+        let later = now + Duration::from_secs(10);
+        assert_eq!(bucket.reserve_at(later), Duration::from_secs(0));
+        assert_eq!(bucket.reserve_at(later), Duration::from_secs(0));
+        assert_eq!(bucket.reserve_at(later), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn should_not_wait_without_limit() {
+        let mut bucket = TokenBucket::new(0.0, 1);
+        for _ in 0..10 {
+            assert_eq!(bucket.reserve(), Duration::from_secs(0));
+        }
+    }
+}
@@ -4,5 +4,10 @@ extern crate serde_derive;
 extern crate log;
 
 pub mod checkers;
+pub mod config;
+pub mod error;
 pub mod fetch;
+#[cfg(test)]
+pub(crate) mod mock;
 pub mod query;
+pub(crate) mod text;
@@ -1,11 +1,22 @@
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use jira_reporter::checkers::analyze::CachePolicy;
+use jira_reporter::checkers::runner::{self, RunOptions};
+use jira_reporter::config::{Config, CONFIG_FILE};
+use jira_reporter::fetch::auth::Auth;
+use jira_reporter::fetch::cassette::Cassette;
 use jira_reporter::fetch::fetcher::Fetcher;
 use jira_reporter::fetch::login::Login;
-use std::rc::Rc;
+use log::error;
+use std::fs;
+use std::io::stdout;
+use std::path::Path;
+use std::process::exit;
 use tokio_core::reactor::Core;
 
 fn main() {
+    let matches = build_cli().get_matches();
     init_logs();
-    run_reports();
+    run_command(&matches);
 }
 
 fn init_logs() {
@@ -20,35 +31,258 @@ fn init_logs() {
         .unwrap_or_else(|_e| panic!("Start log failed!"));
 }
 
-fn run_reports() {
-    use jira_reporter::checkers::{
-        analyze::analyze, ca::cachecker, fs2::fs2checker, sys::syschecker,
+fn build_cli<'a, 'b>() -> App<'a, 'b> {
+    let reports = || {
+        Arg::with_name("reports")
+            .multiple(true)
+            .help("Names of configured reports, all if not given")
     };
 
+    App::new("jira-reporter")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Fetch issues from Jira and generate reports")
+        .setting(AppSettings::VersionlessSubcommands)
+        .arg(
+            Arg::with_name("config")
+                .short("c")
+                .long("config")
+                .global(true)
+                .takes_value(true)
+                .help("Config file path [default: jira-reporter.toml]"),
+        )
+        .arg(
+            Arg::with_name("offline")
+                .long("offline")
+                .global(true)
+                .help("Only use local caches, never connect to Jira"),
+        )
+        .arg(
+            Arg::with_name("user")
+                .short("u")
+                .long("user")
+                .global(true)
+                .takes_value(true)
+                .help("Jira account, otherwise by JIRA_USER or credential file"),
+        )
+        .arg(
+            Arg::with_name("password")
+                .long("password")
+                .global(true)
+                .takes_value(true)
+                .help("Password or token, visible to others, prefer JIRA_PASSWORD"),
+        )
+        .arg(
+            Arg::with_name("record")
+                .long("record")
+                .global(true)
+                .takes_value(true)
+                .conflicts_with("replay")
+                .help("Save all Jira requests and responses to given cassette file, caches are refreshed"),
+        )
+        .arg(
+            Arg::with_name("replay")
+                .long("replay")
+                .global(true)
+                .takes_value(true)
+                .help(
+                    "Answer Jira requests from given cassette file, caches are refreshed \
+                     under --output-dir",
+                ),
+        )
+        .arg(
+            Arg::with_name("output-dir")
+                .short("o")
+                .long("output-dir")
+                .global(true)
+                .takes_value(true)
+                .help("Directory for generated reports, overrides config"),
+        )
+        .subcommand(
+            SubCommand::with_name("fetch")
+                .about("Sync local caches with Jira without generating reports")
+                .arg(reports())
+                .arg(
+                    Arg::with_name("full")
+                        .long("full")
+                        .help("Fetch all issues again instead of the ones updated since last sync"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("report")
+                .about("Generate reports, fetching only when cache is missing or outdated")
+                .arg(reports()),
+        )
+        .subcommand(
+            SubCommand::with_name("query")
+                .about("Search by ad-hoc JQL and print chosen fields as a table")
+                .arg(Arg::with_name("jql").required(true))
+                .arg(
+                    Arg::with_name("fields")
+                        .short("f")
+                        .long("fields")
+                        .takes_value(true)
+                        .default_value("summary,status")
+                        .help("Comma separated field ids"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("diff")
+                .about("Compare an older cache snapshot of a report with a newer one")
+                .arg(Arg::with_name("report").required(true))
+                .arg(Arg::with_name("old").required(true))
+                .arg(Arg::with_name("new").help("Current cache of the report if not given"))
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("Give changes in json instead of text"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("history")
+                .about("Show how an item of a report changed across fetched snapshots")
+                .arg(Arg::with_name("report").required(true))
+                .arg(
+                    Arg::with_name("key")
+                        .required(true)
+                        .help("Issue key, e.g. FPB-1234"),
+                )
+                .arg(
+                    Arg::with_name("days-ago")
+                        .long("days-ago")
+                        .takes_value(true)
+                        .help("Only the state of the item given days ago"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("lint")
+                .about("Check cached items for missing plans, estimations and so on")
+                .arg(reports()),
+        )
+}
+
+//global options given either before or after the sub-command
+fn global_value<'a>(matches: &'a ArgMatches, sub: &'a ArgMatches, name: &str) -> Option<&'a str> {
+    sub.value_of(name).or_else(|| matches.value_of(name))
+}
+
+fn run_command(matches: &ArgMatches) {
+    let empty = ArgMatches::default();
+    let (command, sub) = match matches.subcommand() {
+        (name, Some(sub)) => (name, sub),
+        _ => ("report", &empty),
+    };
+    let config_file = global_value(matches, sub, "config").unwrap_or(CONFIG_FILE);
+    let config = Config::load(config_file)
+        .unwrap_or_else(|e| fail(&format!("Invalid config file {}: {}", config_file, e)));
+    let offline = sub.is_present("offline") || matches.is_present("offline");
+    let replay = global_value(matches, sub, "replay").is_some();
+    let record = global_value(matches, sub, "record").is_some();
+    //a replay never touches real caches, history or reports
+    let config = match global_value(matches, sub, "output-dir") {
+        Some(dir) if replay => {
+            fs::create_dir_all(dir)
+                .unwrap_or_else(|e| fail(&format!("Unable to create {}: {}", dir, e)));
+            config.isolated(dir)
+        }
+        None if replay && command != "query" => {
+            fail("Unable to replay without --output-dir for its caches and reports!")
+        }
+        _ => config,
+    };
+    let selected: Vec<String> = sub
+        .values_of("reports")
+        .map_or_else(Vec::new, |it| it.map(String::from).collect());
     let mut core = Core::new().unwrap();
-    let login = Rc::new(Login::new().to_basic());
-    let mut fetcher = Fetcher::new(login);
-
-    let sys_search = r#"issuetype = "Customer Feature" AND (cf[38700] in (gNB, "Cloud BTS", "AirScale Cloud BTS") OR System in (5G, CloudRAN))"#;
-    //let sys_search = "issuetype = \"Customer Feature\" AND System in (5G, CloudRAN)";
-    let sys_items = analyze(
-        &mut core,
-        &mut fetcher,
-        sys_search,
-        "sys-items.json",
-        syschecker::analyze_results,
-    );
-
-    let fs2_search = "project=FPB AND issuetype in (\"\
-                      Effort Estimation\", \"Entity Technical Analysis\") \
-                      AND \"Competence Area\" = \"MANO MZ\"";
-    let fs2_items = analyze(&mut core, &mut fetcher, fs2_search, "fs2-items.json", |x| {
-        fs2checker::analyze_results(x, &sys_items)
-    });
-
-    let ca_search = "project=FPB AND issuetype = \"\
-                     Competence Area\" AND \"Competence Area\" = \"MANO MZ\"";
-    analyze(&mut core, &mut fetcher, ca_search, "ca-items.json", |x| {
-        cachecker::analyze_result(x, &sys_items, &fs2_items)
-    });
+
+    let result = match command {
+        "fetch" | "report" => {
+            if offline && command == "fetch" {
+                fail("Unable to fetch while working offline!");
+            }
+            let options = RunOptions {
+                policy: match command {
+                    _ if offline => CachePolicy::Offline,
+                    //recorded runs fetch everything, so that a replay finds it
+                    _ if replay || record || sub.is_present("full") => CachePolicy::Refresh,
+                    "fetch" => CachePolicy::Sync,
+                    _ => CachePolicy::Reuse,
+                },
+                render: command == "report",
+                output_dir: global_value(matches, sub, "output-dir").map(String::from),
+                selected,
+            };
+            let mut fetcher = if offline {
+                None
+            } else {
+                Some(create_fetcher(&config, matches, sub))
+            };
+            runner::run_reports(&config, &mut core, fetcher.as_mut(), &options)
+        }
+        "query" => {
+            if offline {
+                fail("Unable to query while working offline!");
+            }
+            use jira_reporter::checkers::adhoc::query_table;
+            let fields = sub.value_of("fields").unwrap().split(',');
+            let fields = fields.map(|it| it.trim().to_string()).collect();
+            let mut fetcher = create_fetcher(&config, matches, sub);
+            let jql = sub.value_of("jql").unwrap();
+            query_table(&config, &mut core, &mut fetcher, jql, fields, &mut stdout())
+        }
+        "diff" => runner::diff_report(
+            &config,
+            sub.value_of("report").unwrap(),
+            sub.value_of("old").unwrap(),
+            sub.value_of("new"),
+            sub.is_present("json"),
+            &mut stdout(),
+        ),
+        "history" => {
+            let days_ago = sub.value_of("days-ago").map(|it| {
+                it.parse()
+                    .unwrap_or_else(|_| fail(&format!("Invalid number of days: {}", it)))
+            });
+            runner::history_report(
+                &config,
+                sub.value_of("report").unwrap(),
+                sub.value_of("key").unwrap(),
+                days_ago,
+                &mut stdout(),
+            )
+        }
+        "lint" => runner::lint_reports(&config, &selected, &mut stdout()).map(|found| {
+            if found > 0 {
+                exit(1);
+            }
+        }),
+        _ => unreachable!(),
+    };
+    result.unwrap_or_else(|e| fail(&format!("Failed to {}: {}", command, e)));
+}
+
+fn create_fetcher(config: &Config, matches: &ArgMatches, sub: &ArgMatches) -> Fetcher {
+    if let Some(path) = global_value(matches, sub, "replay") {
+        return Cassette::replay(Path::new(path))
+            .and_then(|cassette| Ok(Fetcher::new(Auth::Anonymous, config)?.with_cassette(cassette)))
+            .unwrap_or_else(|e| fail(&format!("Unable to replay: {}", e)));
+    }
+
+    let user = global_value(matches, sub, "user").map(String::from);
+    let password = global_value(matches, sub, "password").map(String::from);
+    let fetcher = Login::new(&config.jira, user, password)
+        .and_then(|login| login.to_auth(config.jira.auth))
+        .and_then(|auth| Fetcher::new(auth, config))
+        .unwrap_or_else(|e| fail(&format!("Unable to connect: {}", e)));
+    match global_value(matches, sub, "record") {
+        Some(path) => Cassette::record(Path::new(path))
+            .map(|cassette| fetcher.with_cassette(cassette))
+            .unwrap_or_else(|e| fail(&format!("Unable to record: {}", e))),
+        None => fetcher,
+    }
+}
+
+fn fail(msg: &str) -> ! {
+    error!("{}", msg);
+    eprintln!("{}", msg);
+    exit(2)
 }
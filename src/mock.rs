@@ -0,0 +1,328 @@
+//in-process jira server for tests, serving fixture issues by /rest/api/2/search
+// with paging, field filtering, injected failures and latency, and the field
+// catalogue by /rest/api/2/field
+//
+//jql is not interpreted except for "updated >=", which only gives issues
+// changed by tests
+
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::config::Config;
+
+const SEARCH_PATH: &str = "/rest/api/2/search";
+const FIELD_PATH: &str = "/rest/api/2/field";
+const SESSION_PATH: &str = "/rest/auth/1/session";
+
+//system fields known by every server
+const SYSTEM_FIELDS: [(&str, &str); 4] = [
+    ("summary", "Summary"),
+    ("status", "Status"),
+    ("description", "Description"),
+    ("timeoriginalestimate", "Original Estimate"),
+];
+
+#[derive(Default)]
+struct State {
+    issues: Vec<Value>,
+    fields: Vec<Value>,
+
+    //jira caps page size no matter what is asked
+    max_results: usize,
+    latency: Duration,
+
+    //statuses to answer instead of the page, by startAt
+    failures: HashMap<usize, VecDeque<u16>>,
+
+    //keys of issues changed by tests
+    updated: HashSet<String>,
+
+    //startAt and jql of search requests in arrival order
+    searches: Vec<usize>,
+    queries: Vec<String>,
+    catalog_requests: usize,
+    active: usize,
+    max_active: usize,
+}
+
+pub struct MockJira {
+    port: u16,
+    state: Arc<Mutex<State>>,
+}
+
+//issue as jira gives it, fields are given as a json object
+pub fn issue(key: &str, fields: Value) -> Value {
+    let mut issue = Map::new();
+    issue.insert("expand".to_string(), Value::from(""));
+    issue.insert("id".to_string(), Value::from(key));
+    issue.insert("self".to_string(), Value::from(""));
+    issue.insert("key".to_string(), Value::from(key));
+    issue.insert("fields".to_string(), fields);
+    Value::Object(issue)
+}
+
+//field as given in the catalogue
+fn field(id: &str, name: &str) -> Value {
+    let mut field = Map::new();
+    field.insert("id".to_string(), Value::from(id));
+    field.insert("name".to_string(), Value::from(name));
+    field.insert(
+        "custom".to_string(),
+        Value::from(id.starts_with("customfield_")),
+    );
+    Value::Object(field)
+}
+
+impl MockJira {
+    pub fn start(issues: Vec<Value>) -> MockJira {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Unable to bind mock server!");
+        let port = listener.local_addr().unwrap().port();
+        let state = Arc::new(Mutex::new(State {
+            issues,
+            fields: SYSTEM_FIELDS
+                .iter()
+                .map(|(id, name)| field(id, name))
+                .collect(),
+            max_results: 1000,
+            ..State::default()
+        }));
+
+        let shared = state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let state = shared.clone();
+                thread::spawn(move || serve(stream, &state));
+            }
+        });
+        MockJira { port, state }
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    //config connecting directly to this server, retrying without real delays
+    pub fn config(&self) -> Config {
+        let mut config = Config::default();
+        config.jira.base_url = self.base_url();
+        config.proxy.url = String::new();
+        config.search.base_delay_ms = 1;
+        config.search.max_delay_ms = 10;
+        config.search.requests_per_second = 0.0;
+        config
+    }
+
+    //custom field given by the catalogue
+    pub fn add_field(&self, id: &str, name: &str) {
+        self.state.lock().unwrap().fields.push(field(id, name));
+    }
+
+    //replace fields of the issue of given key, or add it if there's none
+    pub fn update_issue(&self, key: &str, fields: Value) {
+        let mut state = self.state.lock().unwrap();
+        match state.issues.iter_mut().find(|it| it["key"] == key) {
+            Some(found) => found["fields"] = fields,
+            None => state.issues.push(issue(key, fields)),
+        }
+        state.updated.insert(key.to_string());
+    }
+
+    pub fn remove_issue(&self, key: &str) {
+        self.state
+            .lock()
+            .unwrap()
+            .issues
+            .retain(|it| it["key"] != key);
+    }
+
+    pub fn set_max_results(&self, max_results: usize) {
+        self.state.lock().unwrap().max_results = max_results;
+    }
+
+    pub fn set_latency(&self, latency: Duration) {
+        self.state.lock().unwrap().latency = latency;
+    }
+
+    //answer given status for the next times the page is requested
+    pub fn fail_page(&self, start_at: usize, status: u16, times: usize) {
+        let mut state = self.state.lock().unwrap();
+        let failures = state.failures.entry(start_at).or_default();
+        failures.extend(std::iter::repeat_n(status, times));
+    }
+
+    pub fn get_searches(&self) -> Vec<usize> {
+        self.state.lock().unwrap().searches.clone()
+    }
+
+    pub fn get_queries(&self) -> Vec<String> {
+        self.state.lock().unwrap().queries.clone()
+    }
+
+    pub fn get_catalog_requests(&self) -> usize {
+        self.state.lock().unwrap().catalog_requests
+    }
+
+    //most search requests served at the same time
+    pub fn get_max_active(&self) -> usize {
+        self.state.lock().unwrap().max_active
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+//serve requests of a kept-alive connection until it's closed
+fn serve(stream: TcpStream, state: &Mutex<State>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    while let Some(request) = read_request(&mut reader) {
+        let (status, body) = respond(&request, state);
+        let response = format!(
+            "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        if writer.write_all(response.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+fn read_request<R: BufRead>(reader: &mut R) -> Option<Request> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).ok()? == 0 {
+        return None;
+    }
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.split('?').next()?.to_string();
+
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).ok()?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().ok()?;
+            }
+        }
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+    Some(Request {
+        method,
+        path,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+fn respond(request: &Request, state: &Mutex<State>) -> (u16, String) {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("POST", SEARCH_PATH) => search(&request.body, state),
+        ("GET", FIELD_PATH) => {
+            let mut state = state.lock().unwrap();
+            state.catalog_requests += 1;
+            (200, Value::Array(state.fields.clone()).to_string())
+        }
+        ("POST", SESSION_PATH) => (
+            200,
+            r#"{"session": {"name": "JSESSIONID", "value": "mock"}}"#.to_string(),
+        ),
+        _ => (404, error_body("No such resource")),
+    }
+}
+
+fn search(body: &str, state: &Mutex<State>) -> (u16, String) {
+    let query: Value = match serde_json::from_str(body) {
+        Ok(query) => query,
+        Err(_) => return (400, error_body("Invalid search request")),
+    };
+    let start_at = query["startAt"].as_u64().unwrap_or(0) as usize;
+    let jql = query["jql"].as_str().unwrap_or("").to_string();
+    let latency = {
+        let mut state = state.lock().unwrap();
+        state.searches.push(start_at);
+        state.queries.push(jql.clone());
+        state.active += 1;
+        state.max_active = state.max_active.max(state.active);
+        state.latency
+    };
+    thread::sleep(latency);
+
+    let mut state = state.lock().unwrap();
+    state.active -= 1;
+    if let Some(status) = state
+        .failures
+        .get_mut(&start_at)
+        .and_then(|it| it.pop_front())
+    {
+        return (status, error_body("Injected failure"));
+    }
+
+    let max_results = (query["maxResults"].as_u64().unwrap_or(50) as usize).min(state.max_results);
+    let fields: Vec<&str> = query["fields"]
+        .as_array()
+        .map_or_else(Vec::new, |it| it.iter().filter_map(Value::as_str).collect());
+    let updated_only = jql.contains("updated >=");
+    let found: Vec<&Value> = state
+        .issues
+        .iter()
+        .filter(|it| {
+            !updated_only
+                || it["key"]
+                    .as_str()
+                    .is_some_and(|key| state.updated.contains(key))
+        })
+        .collect();
+    let total = found.len();
+    let issues: Vec<Value> = found
+        .into_iter()
+        .skip(start_at)
+        .take(max_results)
+        .map(|it| filter_fields(it, &fields))
+        .collect();
+
+    let mut page = Map::new();
+    page.insert("expand".to_string(), Value::from("schema,names"));
+    page.insert("startAt".to_string(), Value::from(start_at));
+    page.insert("maxResults".to_string(), Value::from(max_results));
+    page.insert("total".to_string(), Value::from(total));
+    page.insert("issues".to_string(), Value::Array(issues));
+
+    (200, Value::Object(page).to_string())
+}
+
+//only requested fields like jira, null if the issue doesn't have it
+fn filter_fields(issue: &Value, fields: &[&str]) -> Value {
+    if fields.is_empty() {
+        return issue.clone();
+    }
+    let mut filtered = Map::new();
+    for field in fields {
+        let value = issue["fields"].get(*field).cloned();
+        filtered.insert(field.to_string(), value.unwrap_or(Value::Null));
+    }
+    let mut issue = issue.clone();
+    issue["fields"] = Value::Object(filtered);
+    issue
+}
+
+fn error_body(message: &str) -> String {
+    let mut body = Map::new();
+    body.insert("errorMessages".to_string(), Value::from(vec![message]));
+    body.insert("errors".to_string(), Value::Object(Map::new()));
+    Value::Object(body).to_string()
+}
@@ -9,6 +9,14 @@ pub struct Query {
     pub fields: Rc<Vec<String>>,
 }
 
+//paging given by the server, its page size may be smaller than asked
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Paging {
+    pub start_at: usize,
+    pub max_results: usize,
+    pub total: usize,
+}
+
 #[derive(Debug)]
 pub enum QueryError {
     WrongQuery,
@@ -34,22 +42,22 @@ impl Query {
         }
     }
 
-    pub fn create_remaining(&self, total: usize) -> Vec<Query> {
-        let mut round = total / self.maxResults - 1;
-        if total % self.maxResults > 0 {
-            round += 1;
+    //queries of pages after the one given by paging, each one of the page size
+    // used by the server so that no issue is skipped when it caps the size
+    pub fn create_remaining(&self, paging: Paging) -> Vec<Query> {
+        if paging.max_results == 0 {
+            warn!("Server gives no page size, no more pages asked!");
+            return Vec::new();
         }
-
-        let mut remainings = Vec::new();
-        for it in 1..=round {
-            remainings.push(Query {
+        let next = paging.start_at + paging.max_results;
+        (next..paging.total)
+            .step_by(paging.max_results)
+            .map(|start_at| Query {
                 jql: self.jql.clone(),
-                startAt: it * self.maxResults,
-                maxResults: self.maxResults,
+                startAt: start_at,
+                maxResults: paging.max_results,
                 fields: self.fields.clone(),
-            });
-        }
-
-        remainings
+            })
+            .collect()
     }
 }
@@ -0,0 +1,209 @@
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+use crate::error::{Error, Result};
+
+//a field as given by /rest/api/2/field
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FieldInfo {
+    //e.g. "summary" or "customfield_37381"
+    pub id: String,
+
+    //shown in UI, e.g. "Feature ID"
+    pub name: String,
+
+    #[serde(default)]
+    pub custom: bool,
+}
+
+//all fields known by the server, custom field ids differ between instances
+// so that fields are declared by names and looked up here
+#[derive(Debug, Clone, Default)]
+pub struct FieldCatalog {
+    fields: Vec<FieldInfo>,
+}
+
+impl FieldCatalog {
+    pub fn new(fields: Vec<FieldInfo>) -> FieldCatalog {
+        FieldCatalog { fields }
+    }
+
+    pub fn parse(json: &str) -> Result<FieldCatalog> {
+        serde_json::from_str(json)
+            .map(FieldCatalog::new)
+            .map_err(|e| Error::Json {
+                context: "field catalogue".to_string(),
+                source: e,
+            })
+    }
+
+    //same format as given by the server, so that it's parsed back likewise
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(&self.fields).map_err(|e| Error::Json {
+            context: "field catalogue".to_string(),
+            source: e,
+        })
+    }
+
+    //id of given field, which is either its id or its name ignoring case
+    pub fn resolve(&self, field: &str) -> Result<&str> {
+        if let Some(info) = self.fields.iter().find(|it| it.id == field) {
+            return Ok(&info.id);
+        }
+        let found: Vec<&FieldInfo> = self
+            .fields
+            .iter()
+            .filter(|it| it.name.eq_ignore_ascii_case(field))
+            .collect();
+        match found.as_slice() {
+            [info] => Ok(&info.id),
+            [] => Err(Error::Field(format!("no field named \"{}\"", field))),
+            //jira allows custom fields of the same name
+            _ => {
+                let ids: Vec<&str> = found.iter().map(|it| it.id.as_str()).collect();
+                Err(Error::Field(format!(
+                    "\"{}\" is ambiguous, use one of {} instead",
+                    field,
+                    ids.join(",")
+                )))
+            }
+        }
+    }
+
+    //fields declared by their own names
+    pub fn resolve_all(&self, fields: &[String]) -> Result<FieldMap> {
+        let keyed: Vec<(String, String)> =
+            fields.iter().map(|it| (it.clone(), it.clone())).collect();
+        self.resolve_keyed(&keyed)
+    }
+
+    //fields given as (key, name or id), which are declared by their keys
+    pub fn resolve_keyed(&self, fields: &[(String, String)]) -> Result<FieldMap> {
+        let mut ids = Vec::with_capacity(fields.len());
+        for (key, field) in fields {
+            let id = self.resolve(field)?;
+            debug!("Field \"{}\" of {} resolved to {}", field, key, id);
+            ids.push((id.to_string(), key.clone()));
+        }
+        Ok(FieldMap { ids })
+    }
+}
+
+//ids of fields to search, with the names they're declared by
+#[derive(Debug, Clone, Default)]
+pub struct FieldMap {
+    ids: Vec<(String, String)>,
+}
+
+impl FieldMap {
+    //fields given by their ids, responses are kept as they are
+    pub fn by_ids(ids: &[&str]) -> FieldMap {
+        FieldMap {
+            ids: ids
+                .iter()
+                .map(|it| (it.to_string(), it.to_string()))
+                .collect(),
+        }
+    }
+
+    pub fn get_ids(&self) -> Vec<String> {
+        self.ids.iter().map(|(id, _)| id.clone()).collect()
+    }
+
+    //search response with issue fields keyed by declared names instead of ids,
+    // so that they're deserialized by names
+    pub fn rename_page<'a>(&self, body: &'a str) -> Cow<'a, str> {
+        //a field may be declared by several names
+        let renamed: Vec<(&str, &str)> = self
+            .ids
+            .iter()
+            .filter(|(id, name)| id != name)
+            .map(|(id, name)| (id.as_str(), name.as_str()))
+            .collect();
+        let declared: HashSet<&str> = self.ids.iter().map(|(_, name)| name.as_str()).collect();
+        if renamed.is_empty() {
+            return Cow::Borrowed(body);
+        }
+        //invalid ones are left for the parser to report
+        let mut page: Value = match serde_json::from_str(body) {
+            Ok(page) => page,
+            Err(_) => return Cow::Borrowed(body),
+        };
+        let issues = page
+            .get_mut("issues")
+            .and_then(Value::as_array_mut)
+            .into_iter()
+            .flatten();
+        for fields in issues.filter_map(|it| it.get_mut("fields").and_then(Value::as_object_mut)) {
+            let values: HashMap<&str, Value> = renamed
+                .iter()
+                .filter_map(|(id, _)| fields.get(*id).map(|it| (*id, it.clone())))
+                .collect();
+            for id in values.keys().filter(|it| !declared.contains(*it)) {
+                fields.remove(*id);
+            }
+            for (id, name) in &renamed {
+                if let Some(value) = values.get(id) {
+                    fields.insert(name.to_string(), value.clone());
+                }
+            }
+        }
+        Cow::Owned(page.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog() -> FieldCatalog {
+        FieldCatalog::parse(
+            r#"[{"id": "summary", "name": "Summary", "custom": false},
+                {"id": "customfield_37381", "name": "Feature ID", "custom": true},
+                {"id": "customfield_1", "name": "Team", "custom": true},
+                {"id": "customfield_2", "name": "Team", "custom": true}]"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn should_resolve_by_id_or_name() {
+        let catalog = catalog();
+        assert_eq!(catalog.resolve("summary").unwrap(), "summary");
+        assert_eq!(catalog.resolve("feature id").unwrap(), "customfield_37381");
+        assert_eq!(catalog.resolve("customfield_2").unwrap(), "customfield_2");
+        match catalog.resolve("Team") {
+            Err(Error::Field(reason)) => assert!(reason.contains("customfield_1,customfield_2")),
+            other => panic!("not ambiguous: {:?}", other),
+        }
+        assert!(catalog.resolve("Start FB").is_err());
+    }
+
+    #[test]
+    fn should_rename_issue_fields() {
+        let fields = vec!["summary".to_string(), "Feature ID".to_string()];
+        let map = catalog().resolve_all(&fields).unwrap();
+        assert_eq!(map.get_ids(), vec!["summary", "customfield_37381"]);
+
+        let page = r#"{"total": 1, "issues": [{"key": "FPB-1",
+            "fields": {"summary": "x", "customfield_37381": "F1"}}]}"#;
+        let page: Value = serde_json::from_str(&map.rename_page(page)).unwrap();
+        assert_eq!(page["issues"][0]["fields"]["Feature ID"], "F1");
+        assert_eq!(page["issues"][0]["fields"]["summary"], "x");
+        assert_eq!(page["issues"][0]["fields"].get("customfield_37381"), None);
+        assert_eq!(FieldMap::by_ids(&["summary"]).rename_page("{"), "{");
+    }
+
+    #[test]
+    fn should_declare_fields_by_keys() {
+        let fields = vec![("feature_id".to_string(), "Feature ID".to_string())];
+        let map = catalog().resolve_keyed(&fields).unwrap();
+        let page = r#"{"issues": [{"key": "FPB-1", "fields": {"customfield_37381": "F1"}}]}"#;
+        let page: Value = serde_json::from_str(&map.rename_page(page)).unwrap();
+        assert_eq!(page["issues"][0]["fields"]["feature_id"], "F1");
+
+        let saved = FieldCatalog::parse(&catalog().to_json().unwrap()).unwrap();
+        assert_eq!(saved.resolve("feature id").unwrap(), "customfield_37381");
+    }
+}
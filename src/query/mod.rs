@@ -1,11 +1,13 @@
 pub mod batch;
+pub mod field;
 pub mod issue;
 pub mod result;
 
 // Tests for this module
 #[cfg(test)]
 mod test {
-    use super::batch::Query;
+    use super::batch::{Paging, Query};
+    use quickcheck::{quickcheck, TestResult};
 
     fn create_query() -> (Query, String) {
         (
@@ -32,7 +34,11 @@ mod test {
     #[test]
     fn shall_create_paged_remaining() {
         let (qry, base_json) = create_query();
-        let remainings = qry.create_remaining(300);
+        let remainings = qry.create_remaining(Paging {
+            start_at: 0,
+            max_results: 100,
+            total: 300,
+        });
         assert_eq!(remainings.len(), 2);
 
         assert_eq!(
@@ -44,4 +50,75 @@ mod test {
             remainings[1].to_json().unwrap()
         );
     }
+
+    fn paging(max_results: usize, total: usize) -> Paging {
+        Paging {
+            start_at: 0,
+            max_results,
+            total,
+        }
+    }
+
+    #[test]
+    fn shall_create_nothing_for_single_page() {
+        let (qry, _) = create_query();
+        assert!(qry.create_remaining(paging(100, 0)).is_empty());
+        assert!(qry.create_remaining(paging(100, 42)).is_empty());
+        assert!(qry.create_remaining(paging(100, 100)).is_empty());
+        assert_eq!(qry.create_remaining(paging(100, 101)).len(), 1);
+        assert!(qry.create_remaining(paging(0, 300)).is_empty());
+    }
+
+    #[test]
+    fn shall_follow_page_size_capped_by_server() {
+        let (qry, _) = create_query();
+        let remainings = qry.create_remaining(paging(50, 120));
+        let pages: Vec<(usize, usize)> = remainings
+            .iter()
+            .map(|it| (it.startAt, it.maxResults))
+            .collect();
+        assert_eq!(pages, vec![(50, 50), (100, 50)]);
+    }
+
+    //first page and remaining ones cover all issues once, whatever the total
+    // and page size given by the server
+    #[test]
+    fn shall_cover_all_issues_once() {
+        fn covered(total: u16, max_results: u16, asked: u16) -> TestResult {
+            let (total, max_results) = (total as usize, max_results as usize % 1000 + 1);
+            let qry = Query::new("Project = FPB".to_string(), asked as usize, Vec::new());
+            let mut starts = vec![0];
+            starts.extend(
+                qry.create_remaining(paging(max_results, total))
+                    .iter()
+                    .map(|it| it.startAt),
+            );
+            let contiguous = starts.windows(2).all(|it| it[1] == it[0] + max_results);
+            let last = *starts.last().unwrap();
+            TestResult::from_bool(
+                contiguous && last + max_results >= total && (last < total || last == 0),
+            )
+        }
+        quickcheck(covered as fn(u16, u16, u16) -> TestResult);
+    }
+
+    #[test]
+    fn shall_continue_after_given_page() {
+        fn continued(start_at: u16, max_results: u16, total: u16) -> TestResult {
+            let max_results = max_results as usize % 1000 + 1;
+            let paging = Paging {
+                start_at: start_at as usize,
+                max_results,
+                total: total as usize,
+            };
+            let (qry, _) = create_query();
+            let remainings = qry.create_remaining(paging);
+            TestResult::from_bool(remainings.iter().all(|it| {
+                it.startAt > paging.start_at
+                    && it.startAt < paging.total
+                    && it.maxResults == max_results
+            }))
+        }
+        quickcheck(continued as fn(u16, u16, u16) -> TestResult);
+    }
 }
@@ -3,6 +3,8 @@ extern crate serde_json;
 
 use self::serde::de::DeserializeOwned;
 use self::serde::Deserialize;
+use super::batch::Paging;
+use crate::error::{Error, Result};
 
 #[derive(Deserialize, Clone)]
 #[allow(non_snake_case, dead_code)]
@@ -12,8 +14,8 @@ pub struct QueryResult<T> {
 
     pub startAt: usize,
 
-    //not used
-    maxResults: usize,
+    //page size used by the server, may be less than asked
+    pub maxResults: usize,
 
     //total records
     pub total: usize,
@@ -23,13 +25,13 @@ pub struct QueryResult<T> {
     pub issues: Vec<T>,
 }
 
-pub fn parse_query_result<T>(json: &str) -> Option<Box<QueryResult<T>>>
+pub fn parse_query_result<T>(json: &str) -> Result<Box<QueryResult<T>>>
 where
     T: DeserializeOwned,
 {
     let qry_result = serde_json::from_str::<QueryResult<T>>(&json);
     match qry_result {
-        Ok(result) => Some(Box::new(result)),
+        Ok(result) => Ok(Box::new(result)),
         Err(err) => {
             let ctx_len = 800;
             let start = if err.column() < ctx_len {
@@ -42,9 +44,13 @@ where
             } else {
                 err.column() + ctx_len
             };
-            let ctx = &json[start..end];
+            //column may fall inside a multi-byte character
+            let ctx = json.get(start..end).unwrap_or(json);
             error!("Parse json failed, err={}, context={}", err, ctx);
-            None
+            Err(Error::Json {
+                context: ctx.to_string(),
+                source: err,
+            })
         }
     }
 }
@@ -59,6 +65,14 @@ impl<T: DeserializeOwned> QueryResult<T> {
         self.issues.extend(other.issues);
     }
 
+    pub fn get_paging(&self) -> Paging {
+        Paging {
+            start_at: self.startAt,
+            max_results: self.maxResults,
+            total: self.total,
+        }
+    }
+
     //generate default
     pub fn default(max: usize) -> QueryResult<T> {
         QueryResult {
@@ -0,0 +1,53 @@
+//text of a markup page without its tags, styles and scripts, e.g. an error
+// page of a proxy, other text is kept as it is
+fn strip_tags(raw: &str) -> String {
+    if !raw.trim_start().starts_with('<') {
+        return raw.to_string();
+    }
+    let mut text = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        text.push(' ');
+        let tag = &rest[start..];
+        let end = tag.find('>').map_or(tag.len(), |it| it + 1);
+        let name = tag[1..end]
+            .trim_start_matches('/')
+            .split(|it: char| it.is_whitespace() || it == '>' || it == '/')
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        rest = &tag[end..];
+        //contents of these aren't shown
+        if !tag.starts_with("</") && (name == "style" || name == "script") {
+            let close = format!("</{}", name);
+            let skipped = rest.to_ascii_lowercase().find(&close).unwrap_or(rest.len());
+            rest = &rest[skipped..];
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+//one line summary of a body in given characters at most, whitespace and line
+// breaks collapsed to single spaces
+pub fn get_summary(raw: &str, total: usize) -> String {
+    let text = strip_tags(raw);
+    let words: Vec<&str> = text.split_whitespace().collect();
+    words.join(" ").chars().take(total).collect()
+}
+
+//Get a slice of the leftmost given characters
+pub fn get_leftmost(raw: &str, total: usize) -> &str {
+    let max = raw.find('\n').map_or(raw.len(), |x| x);
+    if max > total {
+        let mut end = total;
+        //find next char boundary
+        while !raw.is_char_boundary(end) {
+            end += 1;
+        }
+        &raw[0..end]
+    } else {
+        &raw[0..max]
+    }
+}